@@ -0,0 +1,60 @@
+// Rough timing of network propagation, as run by every agent on every tick
+// Run with `cargo bench --no-default-features --bench propagate`
+
+use genetic::neural::{Network, Workspace};
+use rand::SeedableRng;
+use rand_pcg::Pcg64Mcg;
+use std::hint::black_box;
+use std::time::Instant;
+
+const AGENTS: usize = 30;
+const TICKS: usize = 100_000;
+const TOPOLOGY: [usize; 3] = [9, 15, 2];
+
+fn report(name: &str, start: Instant, propagations: usize) {
+    let elapsed = start.elapsed();
+    println!(
+        "{name:<24} {:>8.1} ns/propagation ({propagations} in {:.2?})",
+        elapsed.as_nanos() as f64 / propagations as f64,
+        elapsed
+    );
+}
+
+fn main() {
+    let mut rng = Pcg64Mcg::seed_from_u64(0);
+    let networks: Vec<Network> = (0..AGENTS)
+        .map(|_| Network::random(&mut rng, &TOPOLOGY))
+        .collect();
+    let inputs: Vec<f32> = (0..TOPOLOGY[0] * AGENTS)
+        .map(|i| (i % 17) as f32 / 17.0)
+        .collect();
+
+    let start = Instant::now();
+    for _ in 0..TICKS {
+        for (network, inputs) in networks.iter().zip(inputs.chunks(TOPOLOGY[0])) {
+            black_box(network.propagate(black_box(inputs)));
+        }
+    }
+    report("propagate", start, TICKS * AGENTS);
+
+    // What Control does, reusing a workspace between agents and ticks
+    let mut workspace = Workspace::default();
+    let start = Instant::now();
+    for _ in 0..TICKS {
+        for (network, inputs) in networks.iter().zip(inputs.chunks(TOPOLOGY[0])) {
+            black_box(network.propagate_batch(black_box(inputs), &mut workspace));
+        }
+    }
+    report("propagate_batch (1)", start, TICKS * AGENTS);
+
+    // Every agent's inputs through a single network at once
+    let start = Instant::now();
+    for _ in 0..TICKS {
+        black_box(networks[0].propagate_batch(black_box(&inputs), &mut workspace));
+    }
+    report(
+        &format!("propagate_batch ({AGENTS})"),
+        start,
+        TICKS * AGENTS,
+    );
+}
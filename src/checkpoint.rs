@@ -0,0 +1,500 @@
+use crate::components::{
+    Age, Agent, Behavior, Elite, EpisodeResults, MutationParams, Performance, Position, Score,
+    Target, Velocity,
+};
+use crate::config::Config;
+use crate::fitness::FitnessTerms;
+use crate::hall_of_fame::{self, HallOfFame};
+use crate::neural::io::{
+    check_binary_header, invalid_data, read_f32, read_u32, read_u64, save_atomically,
+};
+use crate::neural::Network;
+use crate::novelty::{self, NoveltyArchive};
+use crate::resources::{Generation, GenerationEvent, Ticks, WorldRng};
+use crate::simulation;
+use specs::{prelude::*, World, WorldExt};
+use std::fs::File;
+use std::io::{self, BufReader, Read, Write};
+use std::path::Path;
+
+// A checkpoint holds everything needed to carry on a run exactly where it stopped: the run's config,
+// the tick, the RNG state, every target and every agent (including its network)
+// Entities are written in the order the world stores them, and are recreated in that same order on resume
+// (obstacles from the config, then targets, then agents) as systems like ResetPositions draw from the RNG in it
+//
+// Binary, version 1 (see neural::io for how versions are kept), little-endian:
+//
+//   4 bytes   magic, "GCKP"
+//   u32       format version
+//   u64       ticks
+//   u64       generations ended so far
+//   u64       episodes of the current generation ended so far, and ticks into its current episode
+//             and into the generation
+//   u8        what the last tick ended, 0 nothing, 1 an episode, 2 a generation (and its last episode)
+//   u32 + ..  length and UTF-8 text of the config as TOML
+//   u32 + ..  length and UTF-8 text of the RNG state as JSON
+//   u32       number of targets, followed by each target's u8 1 if it's in the arena else 0, f32 x and y
+//   u32       number of agents, followed by each agent's f32 x, y, heading and speed,
+//             u32 score, f32 mutation rate and sigma, u8 1 if elite else 0,
+//             f32 distance, energy and seconds spinning, u8 1 if it has hit a target else 0,
+//             f32 seconds to its first hit (0 without one), u32 length and f32 values of its
+//             behaviour descriptor, u64 age in ticks, u32 number of episodes ended so far this
+//             generation followed by each one's f32 hits, distance, energy, time to first hit,
+//             seconds spinning and novelty, and network (see neural::io)
+//   ..        the hall of fame's capacity and members (see hall_of_fame)
+//   ..        the novelty archive's capacity and descriptors (see novelty)
+//
+// Steady-state rolling statistics aren't saved, they start over on resume
+
+const MAGIC: &[u8; 4] = b"GCKP";
+const VERSION: u32 = 1;
+
+struct SavedAgent {
+    position: Position,
+    velocity: Velocity,
+    score: u32,
+    mutation: MutationParams,
+    elite: bool,
+    performance: Performance,
+    behavior: Behavior,
+    age: Age,
+    results: EpisodeResults,
+    network: Network,
+}
+
+pub struct Checkpoint {
+    pub config: Config,
+    pub ticks: u64,
+    pub generation: u64,
+    lifecycle: Generation,
+    rng: WorldRng,
+    // None for targets taken out of the arena until the next episode
+    targets: Vec<Option<Position>>,
+    agents: Vec<SavedAgent>,
+    hall_of_fame: HallOfFame,
+    novelty_archive: NoveltyArchive,
+}
+
+fn write_str<W: Write + ?Sized>(w: &mut W, s: &str) -> io::Result<()> {
+    w.write_all(&(s.len() as u32).to_le_bytes())?;
+    w.write_all(s.as_bytes())
+}
+
+fn read_str<R: Read + ?Sized>(r: &mut R) -> io::Result<String> {
+    let len = read_u32(r)? as u64;
+    let mut s = String::new();
+    r.take(len).read_to_string(&mut s)?;
+    if s.len() as u64 != len {
+        return Err(io::Error::new(
+            io::ErrorKind::UnexpectedEof,
+            "unexpected end of file",
+        ));
+    }
+    Ok(s)
+}
+
+fn read_u8<R: Read + ?Sized>(r: &mut R) -> io::Result<u8> {
+    let mut buf = [0; 1];
+    r.read_exact(&mut buf)?;
+    Ok(buf[0])
+}
+
+fn read_performance<R: Read + ?Sized>(r: &mut R) -> io::Result<Performance> {
+    let (distance, energy, spinning) = (read_f32(r)?, read_f32(r)?, read_f32(r)?);
+    let has_hit = read_u8(r)? != 0;
+    let first_hit = read_f32(r)?;
+    Ok(Performance {
+        distance,
+        energy,
+        first_hit: has_hit.then_some(first_hit),
+        spinning,
+        // Only set at the end of a generation, which then starts over with it reset
+        novelty: 0.0,
+    })
+}
+
+fn read_episode_results<R: Read + ?Sized>(r: &mut R) -> io::Result<EpisodeResults> {
+    let count = read_u32(r)?;
+    let episodes = (0..count)
+        .map(|_| {
+            Ok(FitnessTerms {
+                hits: read_f32(r)?,
+                distance: read_f32(r)?,
+                energy: read_f32(r)?,
+                time_to_first_hit: read_f32(r)?,
+                spinning: read_f32(r)?,
+                novelty: read_f32(r)?,
+            })
+        })
+        .collect::<io::Result<Vec<FitnessTerms>>>()?;
+    Ok(EpisodeResults { episodes })
+}
+
+fn write_f32s<W: Write + ?Sized>(w: &mut W, values: &[f32]) -> io::Result<()> {
+    for v in values {
+        w.write_all(&v.to_le_bytes())?;
+    }
+    Ok(())
+}
+
+// Write a checkpoint of the world as it is between ticks
+pub fn write<W: Write + ?Sized>(w: &mut W, world: &World) -> io::Result<()> {
+    let ticks = world.read_resource::<Ticks>().get();
+    let config = toml::to_string(&*world.read_resource::<Config>())
+        .map_err(|e| invalid_data(format!("unable to write config: {e}")))?;
+    let rng = serde_json::to_string(&world.read_resource::<WorldRng>().0)?;
+
+    w.write_all(MAGIC)?;
+    w.write_all(&VERSION.to_le_bytes())?;
+    w.write_all(&ticks.to_le_bytes())?;
+    let generation = *world.read_resource::<Generation>();
+    w.write_all(&generation.completed().to_le_bytes())?;
+    for value in [
+        generation.episode(),
+        generation.episode_ticks(),
+        generation.ticks(),
+    ] {
+        w.write_all(&value.to_le_bytes())?;
+    }
+    let ended = match generation.last_ended() {
+        None => 0,
+        Some(GenerationEvent::EpisodeEnded) => 1,
+        Some(GenerationEvent::GenerationEnded) => 2,
+    };
+    w.write_all(&[ended])?;
+    write_str(w, &config)?;
+    write_str(w, &rng)?;
+
+    let (
+        positions,
+        velocities,
+        scores,
+        agents,
+        elites,
+        performances,
+        behaviors,
+        ages,
+        results,
+        targets,
+    ) = world.system_data::<(
+        ReadStorage<Position>,
+        ReadStorage<Velocity>,
+        ReadStorage<Score>,
+        ReadStorage<Agent>,
+        ReadStorage<Elite>,
+        ReadStorage<Performance>,
+        ReadStorage<Behavior>,
+        ReadStorage<Age>,
+        ReadStorage<EpisodeResults>,
+        ReadStorage<Target>,
+    )>();
+
+    w.write_all(&(targets.join().count() as u32).to_le_bytes())?;
+    for (p, _) in (positions.maybe(), &targets).join() {
+        w.write_all(&[p.is_some() as u8])?;
+        let p = p.copied().unwrap_or(Position { x: 0.0, y: 0.0 });
+        write_f32s(w, &[p.x, p.y])?;
+    }
+
+    let agents: Vec<_> = (
+        &positions,
+        &velocities,
+        &scores,
+        &agents,
+        elites.maybe(),
+        &performances,
+        &behaviors,
+        &ages,
+        &results,
+    )
+        .join()
+        .collect();
+    w.write_all(&(agents.len() as u32).to_le_bytes())?;
+    for (p, v, s, agent, elite, performance, behavior, age, results) in agents {
+        write_f32s(w, &[p.x, p.y, v.heading, v.magnitude])?;
+        w.write_all(&s.score().to_le_bytes())?;
+        write_f32s(w, &[agent.mutation.rate, agent.mutation.sigma])?;
+        w.write_all(&[elite.is_some() as u8])?;
+        write_f32s(
+            w,
+            &[
+                performance.distance,
+                performance.energy,
+                performance.spinning,
+            ],
+        )?;
+        w.write_all(&[performance.first_hit.is_some() as u8])?;
+        write_f32s(w, &[performance.first_hit.unwrap_or(0.0)])?;
+        novelty::write_descriptor(w, &behavior.descriptor)?;
+        w.write_all(&age.ticks.to_le_bytes())?;
+        w.write_all(&(results.episodes.len() as u32).to_le_bytes())?;
+        for t in &results.episodes {
+            write_f32s(
+                w,
+                &[
+                    t.hits,
+                    t.distance,
+                    t.energy,
+                    t.time_to_first_hit,
+                    t.spinning,
+                    t.novelty,
+                ],
+            )?;
+        }
+        agent.network.write_binary(w)?;
+    }
+
+    world.read_resource::<HallOfFame>().write_members(w)?;
+    world.read_resource::<NoveltyArchive>().write(w)
+}
+
+pub fn read<R: Read + ?Sized>(r: &mut R) -> io::Result<Checkpoint> {
+    check_binary_header(r, MAGIC, VERSION)?;
+
+    let ticks = read_u64(r)?;
+    let generation = read_u64(r)?;
+    let (episode, episode_ticks, generation_ticks) = (read_u64(r)?, read_u64(r)?, read_u64(r)?);
+    let ended = match read_u8(r)? {
+        0 => None,
+        1 => Some(GenerationEvent::EpisodeEnded),
+        2 => Some(GenerationEvent::GenerationEnded),
+        other => return Err(invalid_data(format!("unknown generation event {other}"))),
+    };
+    // The generation that ended on the last tick is still the current one until the next
+    let number = generation - (ended == Some(GenerationEvent::GenerationEnded)) as u64;
+    let lifecycle = Generation::new(number, episode, episode_ticks, generation_ticks, ended);
+    let config: Config = read_str(r)?
+        .parse()
+        .map_err(|e| invalid_data(format!("checkpoint has an {e}")))?;
+    let rng = WorldRng(serde_json::from_str(&read_str(r)?)?);
+
+    let num_targets = read_u32(r)?;
+    let targets = (0..num_targets)
+        .map(|_| {
+            let in_arena = read_u8(r)? != 0;
+            let position = Position {
+                x: read_f32(r)?,
+                y: read_f32(r)?,
+            };
+            Ok(in_arena.then_some(position))
+        })
+        .collect::<io::Result<Vec<Option<Position>>>>()?;
+
+    let num_agents = read_u32(r)?;
+    let agents = (0..num_agents)
+        .map(|_| {
+            Ok(SavedAgent {
+                position: Position {
+                    x: read_f32(r)?,
+                    y: read_f32(r)?,
+                },
+                velocity: Velocity {
+                    heading: read_f32(r)?,
+                    magnitude: read_f32(r)?,
+                },
+                score: read_u32(r)?,
+                mutation: MutationParams {
+                    rate: read_f32(r)?,
+                    sigma: read_f32(r)?,
+                },
+                elite: read_u8(r)? != 0,
+                performance: read_performance(r)?,
+                behavior: Behavior {
+                    descriptor: novelty::read_descriptor(r)?,
+                },
+                age: Age {
+                    ticks: read_u64(r)?,
+                },
+                results: read_episode_results(r)?,
+                network: Network::read_binary(r)?,
+            })
+        })
+        .collect::<io::Result<Vec<SavedAgent>>>()?;
+
+    let hall_of_fame = HallOfFame::read_members(r)?;
+    let novelty_archive = NoveltyArchive::read(r)?;
+
+    Ok(Checkpoint {
+        config,
+        ticks,
+        generation,
+        lifecycle,
+        rng,
+        targets,
+        agents,
+        hall_of_fame,
+        novelty_archive,
+    })
+}
+
+pub fn save<P: AsRef<Path>>(path: P, world: &World) -> io::Result<()> {
+    save_atomically(path, |w| write(w, world))
+}
+
+pub fn load<P: AsRef<Path>>(path: P) -> io::Result<Checkpoint> {
+    read(&mut BufReader::new(File::open(path)?))
+}
+
+// Save a checkpoint if checkpointing is enabled and the world is at the end of a checkpointed generation
+// The hall of fame is saved at the same times, if it has a path of its own
+pub fn save_if_due(world: &World) -> io::Result<()> {
+    let config = world.read_resource::<Config>();
+    let generation = world.read_resource::<Generation>();
+    if config.checkpoint.every_generations == 0
+        || !generation.ended(GenerationEvent::GenerationEnded)
+        || generation.completed() % config.checkpoint.every_generations != 0
+    {
+        return Ok(());
+    }
+
+    save_files(world, &config)
+}
+
+// Save a checkpoint if checkpointing is enabled, for when a run is ending
+pub fn save_on_exit(world: &World) -> io::Result<()> {
+    save_files(world, &world.read_resource::<Config>())
+}
+
+fn save_files(world: &World, config: &Config) -> io::Result<()> {
+    if let Some(path) = &config.checkpoint.path {
+        save(path, world)?;
+    }
+    if let Some(path) = &config.hall_of_fame.path {
+        hall_of_fame::save(path, &world.read_resource::<HallOfFame>())?;
+    }
+    Ok(())
+}
+
+impl Checkpoint {
+    // Recreate the world the checkpoint was taken from
+    pub fn into_world(self) -> World {
+        let seed = self.config.simulation.seed.unwrap_or_default();
+        let mut world = simulation::create_empty_world(&self.config, seed);
+        world.insert(self.rng);
+        world.insert(Ticks::new(self.ticks));
+        world.insert(self.lifecycle);
+        world.insert(self.hall_of_fame);
+        world.insert(self.novelty_archive);
+
+        for position in self.targets {
+            world
+                .create_entity()
+                .with(Target)
+                .maybe_with(position)
+                .build();
+        }
+        for agent in self.agents {
+            let entity = simulation::spawn_agent(
+                &mut world,
+                agent.network,
+                agent.mutation,
+                agent.position,
+                agent.velocity,
+                Score::with_score(agent.score),
+            );
+            world
+                .write_storage::<Performance>()
+                .insert(entity, agent.performance)
+                .expect("Unable to restore performance");
+            world
+                .write_storage::<Behavior>()
+                .insert(entity, agent.behavior)
+                .expect("Unable to restore behaviour");
+            world
+                .write_storage::<Age>()
+                .insert(entity, agent.age)
+                .expect("Unable to restore age");
+            world
+                .write_storage::<EpisodeResults>()
+                .insert(entity, agent.results)
+                .expect("Unable to restore episode results");
+            if agent.elite {
+                world
+                    .write_storage::<Elite>()
+                    .insert(entity, Elite)
+                    .expect("Unable to mark elite");
+            }
+        }
+
+        world
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::simulation::Simulation;
+
+    fn run(simulation: &mut Simulation, ticks: u64) {
+        while simulation.ticks() < ticks {
+            simulation.step().unwrap();
+        }
+    }
+
+    fn population(simulation: &Simulation) -> Vec<u8> {
+        let mut buf = vec![];
+        for network in simulation.networks() {
+            network.write_binary(&mut buf).unwrap();
+        }
+        buf
+    }
+
+    #[test]
+    fn resume_matches_uninterrupted_run() {
+        let config: Config = "[simulation]\ntickrate = 30\npopulation_lifetime_seconds = 1\n\n[selection]\nelites = 2\n\n[fitness]\ndistance = 0.01\nnovelty = 0.5\n\n[hall_of_fame]\nsize = 3\nreinject = 2\n\n[novelty]\nk = 5\n"
+            .parse()
+            .unwrap();
+
+        let mut uninterrupted = Simulation::builder(&config).seed(3).build();
+        run(&mut uninterrupted, 100);
+
+        let mut first = Simulation::builder(&config).seed(3).build();
+        run(&mut first, 45);
+        let mut buf = vec![];
+        write(&mut buf, &first.world).unwrap();
+        let checkpoint = read(&mut buf.as_slice()).unwrap();
+        assert_eq!(checkpoint.ticks, 45);
+        assert_eq!(checkpoint.generation, 1);
+
+        let mut resumed = Simulation::builder(&config).resume(checkpoint).build();
+        run(&mut resumed, 100);
+
+        assert_eq!(population(&uninterrupted), population(&resumed));
+    }
+
+    fn positions(simulation: &Simulation) -> Vec<(f32, f32)> {
+        let positions = simulation.world.read_storage::<Position>();
+        positions.join().map(|p| (p.x, p.y)).collect()
+    }
+
+    #[test]
+    fn resume_matches_uninterrupted_run_without_respawning() {
+        let config: Config = "[arena]\nwidth = 20\nheight = 20\n\n[simulation]\ntickrate = 30\npopulation_lifetime_seconds = 1\nnum_targets = 3\nrespawn_targets = false\n"
+            .parse()
+            .unwrap();
+
+        let mut uninterrupted = Simulation::builder(&config).seed(5).build();
+        run(&mut uninterrupted, 150);
+
+        // Checkpointed once some of the targets are gone
+        let mut first = Simulation::builder(&config).seed(5).build();
+        loop {
+            first.step().unwrap();
+            let (targets, positions) = first
+                .world
+                .system_data::<(ReadStorage<Target>, ReadStorage<Position>)>();
+            if (&targets, &positions).join().count() < 3 {
+                break;
+            }
+        }
+        let mut buf = vec![];
+        write(&mut buf, &first.world).unwrap();
+        let mut resumed = Simulation::builder(&config)
+            .resume(read(&mut buf.as_slice()).unwrap())
+            .build();
+        run(&mut resumed, 150);
+
+        assert_eq!(population(&uninterrupted), population(&resumed));
+        assert_eq!(positions(&uninterrupted), positions(&resumed));
+    }
+}
@@ -0,0 +1,193 @@
+use genetic::config::Config;
+use genetic::population::Format;
+use genetic::simulation::StopAfter;
+use std::path::PathBuf;
+
+pub const USAGE: &str = "\
+Usage: genetic <command> [options]
+
+Commands:
+  train [--headless] [--generations <n> | --ticks <n>] [--save <population> [--binary]] [--record <recording>]
+        [--resume <checkpoint>]
+      evolve a new population, in a window or as fast as possible without one
+      a resumed run carries on with the config saved in its checkpoint
+  watch <population> [--ticks <n>] [--hall-of-fame [--member <n>]]
+      render a saved population without evolving it
+      with --hall-of-fame the file is a saved hall of fame, whose members are listed and watched,
+      all of them or only the one numbered <n>
+  evaluate <population> [--scenarios <n>]
+      score each saved network alone on fixed scenarios
+  replay <recording>
+      play back a recorded run
+
+Config options, accepted by every command:
+  --config <file>        load a TOML run configuration
+  --set <key>=<value>    override any config value, e.g. --set mutation.probability=0.02
+  --width <n>            arena.width
+  --height <n>           arena.height
+  --tickrate <n>         simulation.tickrate
+  --framerate-ratio <n>  simulation.framerate_ratio
+  --targets <n>          simulation.num_targets
+  --agents <n>           simulation.num_agents
+  --lifetime <seconds>   simulation.population_lifetime_seconds
+  --seed <n>             simulation.seed
+  --checkpoint <file>    checkpoint.path";
+
+pub enum Command {
+    Train {
+        headless: bool,
+        stop: Option<StopAfter>,
+        save: Option<PathBuf>,
+        format: Format,
+        record: Option<PathBuf>,
+        resume: Option<PathBuf>,
+    },
+    Watch {
+        population: PathBuf,
+        stop: Option<StopAfter>,
+        hall_of_fame: bool,
+        member: Option<usize>,
+    },
+    Evaluate {
+        population: PathBuf,
+        scenarios: u64,
+    },
+    Replay {
+        recording: PathBuf,
+    },
+}
+
+pub struct Cli {
+    pub command: Command,
+    pub config: Config,
+}
+
+// Shorthands for `--set` of the values that were once hard-coded in main
+const SHORTHANDS: &[(&str, &str)] = &[
+    ("--width", "arena.width"),
+    ("--height", "arena.height"),
+    ("--tickrate", "simulation.tickrate"),
+    ("--framerate-ratio", "simulation.framerate_ratio"),
+    ("--targets", "simulation.num_targets"),
+    ("--agents", "simulation.num_agents"),
+    ("--lifetime", "simulation.population_lifetime_seconds"),
+    ("--seed", "simulation.seed"),
+    ("--checkpoint", "checkpoint.path"),
+];
+
+fn value(args: &mut impl Iterator<Item = String>, flag: &str) -> Result<String, String> {
+    args.next()
+        .ok_or_else(|| format!("{flag} requires a value"))
+}
+
+fn count(args: &mut impl Iterator<Item = String>, flag: &str) -> Result<u64, String> {
+    let v = value(args, flag)?;
+    v.parse()
+        .map_err(|_| format!("{flag} expects a number, got `{v}`"))
+}
+
+pub fn parse<I: IntoIterator<Item = String>>(args: I) -> Result<Cli, String> {
+    let mut args = args.into_iter();
+    let command = args.next().ok_or("missing command")?;
+
+    let mut config_path = None;
+    let mut overrides = vec![];
+    let mut headless = false;
+    let mut stop = None;
+    let mut save = None;
+    let mut format = Format::Text;
+    let mut record = None;
+    let mut resume = None;
+    let mut scenarios = 10;
+    let mut hall_of_fame = false;
+    let mut member = None;
+    let mut positional = vec![];
+
+    while let Some(arg) = args.next() {
+        let flag = arg.as_str();
+        if let Some((_, key)) = SHORTHANDS.iter().find(|(f, _)| *f == flag) {
+            overrides.push(format!("{key}={}", value(&mut args, flag)?));
+            continue;
+        }
+        match (command.as_str(), flag) {
+            (_, "--config") => config_path = Some(PathBuf::from(value(&mut args, flag)?)),
+            (_, "--set") => overrides.push(value(&mut args, flag)?),
+            ("train", "--headless") => headless = true,
+            ("train", "--generations") => {
+                stop = Some(StopAfter::Generations(count(&mut args, flag)?))
+            }
+            ("train" | "watch", "--ticks") => {
+                stop = Some(StopAfter::Ticks(count(&mut args, flag)?))
+            }
+            ("train", "--save") => save = Some(PathBuf::from(value(&mut args, flag)?)),
+            ("train", "--binary") => format = Format::Binary,
+            ("train", "--record") => record = Some(PathBuf::from(value(&mut args, flag)?)),
+            ("train", "--resume") => resume = Some(PathBuf::from(value(&mut args, flag)?)),
+            ("watch", "--hall-of-fame") => hall_of_fame = true,
+            ("watch", "--member") => member = Some(count(&mut args, flag)? as usize),
+            ("evaluate", "--scenarios") => scenarios = count(&mut args, flag)?,
+            (_, f) if f.starts_with('-') => {
+                return Err(format!("unknown option `{f}` for `{command}`"))
+            }
+            _ => positional.push(PathBuf::from(arg)),
+        }
+    }
+
+    let mut file = |what: &str| -> Result<PathBuf, String> {
+        match positional.len() {
+            1 => Ok(positional.remove(0)),
+            0 => Err(format!("`{command}` requires a {what} file")),
+            _ => Err(format!("`{command}` takes a single {what} file")),
+        }
+    };
+
+    let command = match command.as_str() {
+        "train" => {
+            if !positional.is_empty() {
+                return Err("`train` takes no files".to_owned());
+            }
+            Command::Train {
+                headless,
+                stop,
+                save,
+                format,
+                record,
+                resume,
+            }
+        }
+        "watch" => {
+            if member.is_some() && !hall_of_fame {
+                return Err("--member requires --hall-of-fame".to_owned());
+            }
+            Command::Watch {
+                population: file("population")?,
+                stop,
+                hall_of_fame,
+                member,
+            }
+        }
+        "evaluate" => Command::Evaluate {
+            population: file("population")?,
+            scenarios,
+        },
+        "replay" => Command::Replay {
+            recording: file("recording")?,
+        },
+        c => return Err(format!("unknown command `{c}`")),
+    };
+
+    if matches!(
+        command,
+        Command::Train {
+            resume: Some(_),
+            ..
+        }
+    ) && (config_path.is_some() || !overrides.is_empty())
+    {
+        return Err("a resumed run uses the config saved in its checkpoint".to_owned());
+    }
+
+    let config = Config::load_with_overrides(config_path, &overrides).map_err(|e| e.to_string())?;
+
+    Ok(Cli { command, config })
+}
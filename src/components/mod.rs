@@ -1,13 +1,55 @@
+use crate::fitness::FitnessTerms;
 use crate::neural::Network;
+use crate::obstacles::Shape;
+use crate::pareto::ParetoRank;
+use rand::Rng;
+use rand_distr::StandardNormal;
 use specs::{Component, NullStorage, VecStorage};
 
 #[derive(Component, Debug)]
 #[storage(VecStorage)]
 pub struct Agent {
-    // This network should have an odd number of neurons in its first layer
-    // so the agent can have a receptor centered in the direction its heading
+    // This network should have an odd number of neurons in its first layer (or of receptors, half its inputs,
+    // when obstacles are sensed) so the agent can have a receptor centered in the direction its heading
     pub inputs: Option<Vec<f32>>,
     pub network: Network,
+    pub mutation: MutationParams,
+}
+
+// Lowest that self-adaptation can take either parameter, so that it can't stop mutation altogether
+const MIN_MUTATION_PARAM: f32 = 1e-4;
+
+// Highest that self-adaptation can take sigma, far beyond any useful step but short of overflowing
+const MAX_MUTATION_SIGMA: f32 = 100.0;
+
+// How an agent's network is mutated, carried by the agent so that it can evolve along with the network
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct MutationParams {
+    // Chance of each bias and weight being mutated
+    pub rate: f32,
+    // Standard deviation of gaussian mutation
+    pub sigma: f32,
+}
+
+impl MutationParams {
+    // Log-normal self-adaptation, scaling each parameter by exp(learning_rate * N(0, 1))
+    pub fn adapt<R: Rng + ?Sized>(&mut self, rng: &mut R, learning_rate: f32) {
+        let rate_scale = (learning_rate * rng.sample::<f32, _>(StandardNormal)).exp();
+        let sigma_scale = (learning_rate * rng.sample::<f32, _>(StandardNormal)).exp();
+        self.rate = (self.rate * rate_scale).clamp(MIN_MUTATION_PARAM, 1.0);
+        self.sigma = (self.sigma * sigma_scale).clamp(MIN_MUTATION_PARAM, MAX_MUTATION_SIGMA);
+    }
+
+    pub fn crossover_uniform<R: Rng + ?Sized>(&self, rng: &mut R, b: &Self) -> Self {
+        Self {
+            rate: if rng.gen::<bool>() { self.rate } else { b.rate },
+            sigma: if rng.gen::<bool>() {
+                self.sigma
+            } else {
+                b.sigma
+            },
+        }
+    }
 }
 
 #[derive(Component, Debug, Default)]
@@ -21,6 +63,10 @@ impl Score {
         Self { score: 0 }
     }
 
+    pub fn with_score(score: u32) -> Self {
+        Self { score }
+    }
+
     pub fn inc(&mut self) {
         self.score += 1;
     }
@@ -45,6 +91,18 @@ pub struct Force {
 #[storage(NullStorage)]
 pub struct Target;
 
+// A static obstacle, which has a shape of its own instead of a Position
+#[derive(Component, Debug)]
+#[storage(VecStorage)]
+pub struct Obstacle {
+    pub shape: Shape,
+}
+
+// Marks the agents whose networks were carried over unchanged from the last generation
+#[derive(Component, Debug, Default)]
+#[storage(NullStorage)]
+pub struct Elite;
+
 #[derive(Clone, Component, Copy, Debug)]
 #[storage(VecStorage)]
 pub struct Position {
@@ -65,9 +123,83 @@ pub struct Velocity {
     pub magnitude: f32,
 }
 
+// What an agent has done so far in the current generation, besides hitting targets, for its fitness terms
+#[derive(Clone, Component, Debug, Default, PartialEq)]
+#[storage(VecStorage)]
+pub struct Performance {
+    // pixels
+    pub distance: f32,
+    // the integral of |Force| over time
+    pub energy: f32,
+    // seconds into the generation
+    pub first_hit: Option<f32>,
+    // seconds
+    pub spinning: f32,
+    // mean distance to the nearest behaviours, only set once the generation has ended
+    pub novelty: f32,
+}
+
+// Fitness terms of each of the generation's episodes that an agent has finished
+#[derive(Clone, Component, Debug, Default, PartialEq)]
+#[storage(VecStorage)]
+pub struct EpisodeResults {
+    pub episodes: Vec<FitnessTerms>,
+}
+
+impl EpisodeResults {
+    // Targets hit over every episode
+    pub fn hits(&self) -> u32 {
+        self.episodes.iter().map(|e| e.hits as u32).sum()
+    }
+}
+
+// How long an agent has been alive, for steady-state runs where agents are replaced one at a time
+#[derive(Clone, Component, Debug, Default, PartialEq)]
+#[storage(VecStorage)]
+pub struct Age {
+    pub ticks: u64,
+}
+
+// What an agent has done over a generation, described as configured for novelty search
+#[derive(Clone, Component, Debug, Default, PartialEq)]
+#[storage(VecStorage)]
+pub struct Behavior {
+    pub descriptor: Vec<f32>,
+}
+
 // The fitness of a candidate in its population (higher is better)
 #[derive(Component, Debug, Default)]
 #[storage(VecStorage)]
 pub struct Fitness {
     pub fitness: u32,
 }
+
+// An agent's objectives and where they rank, when selection trades objectives off rather than weighing them
+#[derive(Clone, Component, Debug)]
+#[storage(VecStorage)]
+pub struct Objectives {
+    // in the order of the configured objectives, negated where lower is better
+    pub values: Vec<f32>,
+    pub rank: ParetoRank,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::SeedableRng;
+    use rand_pcg::Pcg64Mcg;
+
+    #[test]
+    fn adapt_stays_in_bounds() {
+        let mut rng = Pcg64Mcg::seed_from_u64(7);
+        let mut params = MutationParams {
+            rate: 0.5,
+            sigma: 0.1,
+        };
+        for _ in 0..1000 {
+            params.adapt(&mut rng, 2.0);
+            assert!((MIN_MUTATION_PARAM..=1.0).contains(&params.rate));
+            assert!((MIN_MUTATION_PARAM..=MAX_MUTATION_SIGMA).contains(&params.sigma));
+        }
+    }
+}
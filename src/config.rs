@@ -0,0 +1,1026 @@
+use crate::components::Position;
+use crate::neural::Activation;
+use crate::obstacles::{self, Shape};
+use serde::{Deserialize, Serialize};
+use std::f32::consts::PI;
+use std::path::{Path, PathBuf};
+use std::{fmt, fs, io, str::FromStr};
+
+// Everything that describes a run, loaded from a TOML file and inserted into the world as a resource
+// Every section and key is optional and falls back to its default, but unknown keys are rejected
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    pub arena: ArenaConfig,
+    pub simulation: SimulationConfig,
+    pub network: NetworkConfig,
+    pub fitness: FitnessConfig,
+    pub evaluation: EvaluationConfig,
+    pub novelty: NoveltyConfig,
+    pub selection: SelectionConfig,
+    pub crossover: CrossoverConfig,
+    pub mutation: MutationConfig,
+    pub vision: VisionConfig,
+    pub obstacles: ObstaclesConfig,
+    pub velocity: VelocityConfig,
+    pub checkpoint: CheckpointConfig,
+    pub hall_of_fame: HallOfFameConfig,
+    pub islands: IslandsConfig,
+    pub steady_state: SteadyStateConfig,
+    pub stats: StatsConfig,
+}
+
+// Size of the (wrapping) world, the window is created at the same size
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ArenaConfig {
+    pub width: u32,
+    pub height: u32,
+}
+
+impl Default for ArenaConfig {
+    fn default() -> Self {
+        Self {
+            width: 1200,
+            height: 1200,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct SimulationConfig {
+    pub tickrate: u32,
+    // ratio of framerate to tickrate, render FPS will be tickrate/framerate_ratio
+    pub framerate_ratio: u64,
+    pub num_targets: usize,
+    // when false, hit targets are gone until the next episode, which starts early once they all are
+    pub respawn_targets: bool,
+    pub num_agents: usize,
+    pub population_lifetime_seconds: u64,
+    // seed for every random choice in a run, a random seed is picked (and printed) when unset
+    pub seed: Option<u64>,
+}
+
+impl Default for SimulationConfig {
+    fn default() -> Self {
+        Self {
+            tickrate: 120,
+            framerate_ratio: 1,
+            num_targets: 50,
+            respawn_targets: true,
+            num_agents: 30,
+            population_lifetime_seconds: 60,
+            seed: None,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct NetworkConfig {
+    // Neurons per layer, the first being the number of vision receptors and the last the number of outputs
+    pub topology: Vec<usize>,
+    pub hidden_activation: Activation,
+    // Outputs are mapped from the activation's range onto the agent's controls
+    pub output_activation: Activation,
+}
+
+impl Default for NetworkConfig {
+    fn default() -> Self {
+        Self {
+            topology: vec![9, 15, 2],
+            hidden_activation: Activation::Relu,
+            output_activation: Activation::Relu,
+        }
+    }
+}
+
+impl NetworkConfig {
+    // The activation of every layer after the input layer
+    pub fn activations(&self) -> Vec<Activation> {
+        let mut activations = vec![self.hidden_activation; self.topology.len().saturating_sub(2)];
+        activations.push(self.output_activation);
+        activations
+    }
+}
+
+// Weights of the terms that are summed into an agent's fitness at the end of a generation
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct FitnessConfig {
+    // per target hit
+    pub hits: f32,
+    // per pixel travelled
+    pub distance: f32,
+    // per unit of force applied for a second, rotation and translation together
+    pub energy: f32,
+    // per second before the first hit, the whole generation if there wasn't one
+    pub time_to_first_hit: f32,
+    // per second spent spinning in place
+    pub spinning: f32,
+    // per unit of novelty, how far an agent's behaviour is from its nearest neighbours' (see NoveltyConfig)
+    pub novelty: f32,
+    // an agent is spinning in place while it's slower than `spin_speed` and turning faster than `spin_rotation` radians per second
+    pub spin_speed: f32,
+    pub spin_rotation: f32,
+}
+
+impl Default for FitnessConfig {
+    fn default() -> Self {
+        Self {
+            hits: 1.0,
+            distance: 0.0,
+            energy: 0.0,
+            time_to_first_hit: 0.0,
+            spinning: 0.0,
+            novelty: 0.0,
+            spin_speed: 20.0,
+            spin_rotation: 2.0,
+        }
+    }
+}
+
+// One of the terms of an agent's fitness, for use as an objective on its own
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FitnessTerm {
+    Hits,
+    Distance,
+    Energy,
+    TimeToFirstHit,
+    Spinning,
+    Novelty,
+}
+
+impl FitnessTerm {
+    pub const ALL: [FitnessTerm; 6] = [
+        FitnessTerm::Hits,
+        FitnessTerm::Distance,
+        FitnessTerm::Energy,
+        FitnessTerm::TimeToFirstHit,
+        FitnessTerm::Spinning,
+        FitnessTerm::Novelty,
+    ];
+
+    // Hits, distance and novelty are better the higher they are, the rest the lower
+    pub fn maximised(&self) -> bool {
+        matches!(
+            self,
+            FitnessTerm::Hits | FitnessTerm::Distance | FitnessTerm::Novelty
+        )
+    }
+}
+
+// How the fitness terms of a generation's episodes are combined
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Aggregate {
+    Mean,
+    // the worst episode for each term
+    Min,
+    Median,
+}
+
+// Each generation is made of `episodes` population lifetimes, every one with new target and start positions,
+// so that no agent is selected on a single lucky layout
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct EvaluationConfig {
+    pub episodes: u64,
+    pub aggregate: Aggregate,
+}
+
+impl Default for EvaluationConfig {
+    fn default() -> Self {
+        Self {
+            episodes: 1,
+            aggregate: Aggregate::Mean,
+        }
+    }
+}
+
+// How an agent's behaviour over a generation is described for novelty search
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BehaviorDescriptor {
+    // where it ended the generation
+    FinalPosition,
+    // where it was at `samples` evenly spaced times
+    Trajectory,
+    // how long it spent applying each combination of rotation and translation, in `bins` x `bins` bins
+    Forces,
+}
+
+// Novelty search rewards agents for behaving unlike the rest of the population and the archive of
+// earlier behaviours, it's only run when the novelty term is weighted or is an nsga2 objective
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct NoveltyConfig {
+    pub descriptor: BehaviorDescriptor,
+    // number of nearest neighbours whose mean distance is an agent's novelty
+    pub k: usize,
+    // positions sampled over a generation for the trajectory descriptor
+    pub samples: usize,
+    // bins per force for the forces descriptor
+    pub bins: usize,
+    // number of the most novel behaviours added to the archive every generation
+    pub archive_per_generation: usize,
+    // number of behaviours the archive holds, the oldest are dropped first
+    pub archive_size: usize,
+}
+
+impl Default for NoveltyConfig {
+    fn default() -> Self {
+        Self {
+            descriptor: BehaviorDescriptor::Trajectory,
+            k: 15,
+            samples: 8,
+            bins: 4,
+            archive_per_generation: 2,
+            archive_size: 500,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct SelectionConfig {
+    pub method: SelectionMethod,
+    // Number of agents competing in each tournament
+    pub tournament_size: usize,
+    // Chance of the fittest agent in a tournament winning it, otherwise the next fittest is tried with the same chance and so on
+    pub tournament_probability: f32,
+    // Fraction of the highest scoring agents that truncation selection picks parents from
+    pub truncation_fraction: f32,
+    // Boltzmann selection weighs agents by exp(score / temperature)
+    pub temperature: f32,
+    // Fitness terms that nsga2 selection trades off against each other, instead of summing their weights
+    pub objectives: Vec<FitnessTerm>,
+    // Number of the fittest agents whose networks carry over to the next generation unchanged
+    pub elites: usize,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SelectionMethod {
+    // Parents are picked with a chance proportional to the rank of their score
+    Rank,
+    // Parents are picked with a chance proportional to their score
+    Roulette,
+    // Each parent is the winner of a tournament between randomly picked agents
+    Tournament,
+    // Parents are picked alike from the highest scoring agents
+    Truncation,
+    // Stochastic universal sampling, proportional to score like roulette but with less spread
+    Sus,
+    // Parents are picked with a chance proportional to exp(score / temperature)
+    Boltzmann,
+    // NSGA-II, parents are picked by binary tournaments on Pareto front and then crowding distance over `objectives`
+    Nsga2,
+}
+
+impl Default for SelectionConfig {
+    fn default() -> Self {
+        Self {
+            method: SelectionMethod::Rank,
+            tournament_size: 3,
+            tournament_probability: 1.0,
+            truncation_fraction: 0.5,
+            temperature: 2.0,
+            objectives: vec![FitnessTerm::Hits, FitnessTerm::Energy],
+            elites: 0,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct CrossoverConfig {
+    pub operator: CrossoverOperator,
+    // Number of cut points for k-point crossover
+    pub points: usize,
+    // How far past its parents' values blend crossover can place a child's value, as a fraction of their distance
+    pub alpha: f32,
+    // Distribution index of simulated binary crossover, higher keeps children closer to their parents
+    pub eta: f32,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CrossoverOperator {
+    // Take each bias and weight from either parent
+    Uniform,
+    // Alternate between the parents at `points` random cuts through the whole genome
+    KPoint,
+    // BLX-alpha, a random value around the range spanned by the parents' values
+    Blend,
+    // Simulated binary crossover
+    Sbx,
+    // Take whole neurons (bias and input weights) from either parent
+    Neuron,
+}
+
+impl Default for CrossoverConfig {
+    fn default() -> Self {
+        Self {
+            operator: CrossoverOperator::Uniform,
+            points: 2,
+            alpha: 0.5,
+            eta: 15.0,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct MutationConfig {
+    // Chance of each bias and weight being mutated
+    pub probability: f32,
+    pub operator: MutationOperator,
+    // Standard deviation of the noise added by gaussian mutation
+    pub sigma: f32,
+    // [min, max] that gaussian mutation keeps values within, unbounded when unset
+    pub bounds: Option<(f32, f32)>,
+    // Let each agent evolve its own probability and sigma, starting from the values above
+    pub self_adaptive: bool,
+    // How quickly self-adaptive parameters change, each is scaled by exp(learning_rate * N(0, 1)) every generation
+    pub learning_rate: f32,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MutationOperator {
+    // Replace the value with a new one from Uniform(-1, 1)
+    Uniform,
+    // Add N(0, sigma) noise to the value
+    Gaussian,
+}
+
+impl Default for MutationConfig {
+    fn default() -> Self {
+        Self {
+            probability: 0.0125,
+            operator: MutationOperator::Uniform,
+            sigma: 0.1,
+            bounds: None,
+            self_adaptive: false,
+            learning_rate: 0.2,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct VisionConfig {
+    // distance that an agent can see a target
+    pub viewing_distance: f32,
+    // agent field of view in radians
+    pub vision_cone: f32,
+}
+
+impl Default for VisionConfig {
+    fn default() -> Self {
+        Self {
+            viewing_distance: 800.0,
+            vision_cone: PI,
+        }
+    }
+}
+
+// Static obstacles in the arena, which agents can't pass through or see through
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ObstaclesConfig {
+    pub collision: ObstacleCollision,
+    // give networks a second input per vision receptor for how near the obstacle it points at is,
+    // so network.topology starts with twice as many inputs as there are receptors
+    pub sense: bool,
+    pub shapes: Vec<Shape>,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ObstacleCollision {
+    // Stop where the agent meets the obstacle
+    #[default]
+    Stop,
+    // Carry on along the obstacle's surface with whatever part of the move isn't into it
+    Slide,
+}
+
+// Ranges that agent speeds are sampled from, as [min, max)
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct VelocityConfig {
+    // when agents are first created
+    pub initial_magnitude: (f32, f32),
+    // when a new generation starts
+    pub reset_magnitude: (f32, f32),
+}
+
+impl Default for VelocityConfig {
+    fn default() -> Self {
+        Self {
+            initial_magnitude: (5.0, 150.0),
+            reset_magnitude: (5.0, 100.0),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct CheckpointConfig {
+    // where to save checkpoints, checkpointing is disabled when unset
+    pub path: Option<PathBuf>,
+    // how often to save a checkpoint, 0 to only save when the run ends
+    pub every_generations: u64,
+}
+
+impl Default for CheckpointConfig {
+    fn default() -> Self {
+        Self {
+            path: None,
+            every_generations: 10,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct HallOfFameConfig {
+    // number of the best networks ever seen to keep, 0 to disable
+    pub size: usize,
+    // number of offspring replaced by copies of random members every generation
+    pub reinject: usize,
+    // where to save the hall of fame, at the same times as checkpoints
+    pub path: Option<PathBuf>,
+}
+
+// Which islands each island's migrants go to
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MigrationTopology {
+    // the next island along, the last sending to the first
+    Ring,
+    // every other island
+    FullyConnected,
+}
+
+// Independent populations evolved side by side, whose fittest agents migrate between them
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct IslandsConfig {
+    // number of populations, each with `simulation.num_agents` agents and its own targets
+    pub count: usize,
+    // how often (in generations) the fittest agents migrate, 0 for never
+    pub migration_interval: u64,
+    // number of agents each island sends to each island it's connected to
+    pub migrants: usize,
+    pub topology: MigrationTopology,
+}
+
+impl Default for IslandsConfig {
+    fn default() -> Self {
+        Self {
+            count: 1,
+            migration_interval: 5,
+            migrants: 2,
+            topology: MigrationTopology::Ring,
+        }
+    }
+}
+
+impl IslandsConfig {
+    // Number of islands that each island's migrants go to, and that each island takes migrants from
+    pub fn neighbours(&self) -> usize {
+        match self.topology {
+            MigrationTopology::Ring => self.count.min(2) - 1,
+            MigrationTopology::FullyConnected => self.count - 1,
+        }
+    }
+}
+
+// Evolution without generations: each agent lives for a while, then is replaced in place by a child of
+// living parents while everyone else keeps running
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct SteadyStateConfig {
+    pub enabled: bool,
+    // how long each agent lives, the first agents start at random ages so that they don't all expire together
+    pub lifetime_seconds: u64,
+    // number of the most recent lifetimes that rolling statistics are taken over
+    pub window: usize,
+    // how often to print rolling statistics
+    pub report_seconds: u64,
+}
+
+impl Default for SteadyStateConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            lifetime_seconds: 60,
+            window: 100,
+            report_seconds: 10,
+        }
+    }
+}
+
+impl SteadyStateConfig {
+    pub fn lifetime_ticks(&self, tickrate: u32) -> u64 {
+        self.lifetime_seconds * tickrate as u64
+    }
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct StatsConfig {
+    // print a line per generation to stdout
+    pub print: bool,
+    // files to append a line of statistics per generation to
+    pub csv: Option<PathBuf>,
+    pub jsonl: Option<PathBuf>,
+}
+
+impl Default for StatsConfig {
+    fn default() -> Self {
+        Self {
+            print: true,
+            csv: None,
+            jsonl: None,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum ConfigError {
+    Io(io::Error),
+    Parse(toml::de::Error),
+    Invalid(String),
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ConfigError::Io(e) => write!(f, "unable to read config: {e}"),
+            ConfigError::Parse(e) => write!(f, "unable to parse config: {e}"),
+            ConfigError::Invalid(msg) => write!(f, "invalid config: {msg}"),
+        }
+    }
+}
+
+impl std::error::Error for ConfigError {}
+
+impl From<io::Error> for ConfigError {
+    fn from(e: io::Error) -> Self {
+        ConfigError::Io(e)
+    }
+}
+
+impl From<toml::de::Error> for ConfigError {
+    fn from(e: toml::de::Error) -> Self {
+        ConfigError::Parse(e)
+    }
+}
+
+fn invalid(msg: impl Into<String>) -> ConfigError {
+    ConfigError::Invalid(msg.into())
+}
+
+fn check_range(name: &str, (min, max): (f32, f32)) -> Result<(), ConfigError> {
+    if !(min >= 0.0 && min < max && max.is_finite()) {
+        return Err(invalid(format!(
+            "{name} must be a finite range [min, max) with 0 <= min < max, got [{min}, {max}]"
+        )));
+    }
+    Ok(())
+}
+
+// Set a dotted `key=value` pair (e.g. `simulation.num_agents=50`) in a parsed config table
+// The value is read as TOML, falling back to a plain string so that quotes can be left off
+fn apply_override(table: &mut toml::Table, assignment: &str) -> Result<(), ConfigError> {
+    let (key, value) = assignment
+        .split_once('=')
+        .ok_or_else(|| invalid(format!("expected key=value, got `{assignment}`")))?;
+    let value = match format!("value = {value}").parse::<toml::Table>() {
+        Ok(mut parsed) => parsed.remove("value").unwrap(),
+        Err(_) => toml::Value::String(value.to_owned()),
+    };
+
+    let mut path: Vec<&str> = key.trim().split('.').collect();
+    let last = path.pop().filter(|k| !k.is_empty());
+    let last = last.ok_or_else(|| invalid(format!("missing key in `{assignment}`")))?;
+    let mut table = table;
+    for section in path {
+        table = table
+            .entry(section)
+            .or_insert_with(|| toml::Value::Table(toml::Table::new()))
+            .as_table_mut()
+            .ok_or_else(|| invalid(format!("`{section}` in `{key}` is not a section")))?;
+    }
+    table.insert(last.to_owned(), value);
+
+    Ok(())
+}
+
+impl Config {
+    // Load from a file (or the defaults without one), then apply `key=value` overrides on top
+    pub fn load_with_overrides<P: AsRef<Path>>(
+        path: Option<P>,
+        overrides: &[String],
+    ) -> Result<Self, ConfigError> {
+        let mut table = match path {
+            Some(path) => fs::read_to_string(path)?.parse::<toml::Table>()?,
+            None => toml::Table::new(),
+        };
+        for assignment in overrides {
+            apply_override(&mut table, assignment)?;
+        }
+        Self::from_table(table)
+    }
+
+    fn from_table(table: toml::Table) -> Result<Self, ConfigError> {
+        let config: Config = toml::Value::Table(table).try_into()?;
+        config.validate()?;
+        Ok(config)
+    }
+
+    // Check that values are usable, so that a bad file is reported up front instead of panicking mid-run
+    pub fn validate(&self) -> Result<(), ConfigError> {
+        if self.arena.width == 0 || self.arena.height == 0 {
+            return Err(invalid(
+                "arena.width and arena.height must be greater than 0",
+            ));
+        }
+        if self.arena.width > i16::MAX as u32 || self.arena.height > i16::MAX as u32 {
+            return Err(invalid(format!(
+                "arena.width and arena.height must be at most {}",
+                i16::MAX
+            )));
+        }
+
+        let sim = &self.simulation;
+        if sim.tickrate == 0 {
+            return Err(invalid("simulation.tickrate must be greater than 0"));
+        }
+        if sim.framerate_ratio == 0 {
+            return Err(invalid("simulation.framerate_ratio must be greater than 0"));
+        }
+        if sim.num_agents == 0 {
+            return Err(invalid("simulation.num_agents must be greater than 0"));
+        }
+        if sim.population_lifetime_seconds == 0 {
+            return Err(invalid(
+                "simulation.population_lifetime_seconds must be greater than 0",
+            ));
+        }
+
+        let topology = &self.network.topology;
+        if topology.len() < 2 {
+            return Err(invalid(
+                "network.topology must have at least an input and an output layer",
+            ));
+        }
+        if topology.contains(&0) {
+            return Err(invalid("network.topology layers must not be empty"));
+        }
+        if topology.last() != Some(&2) {
+            return Err(invalid(
+                "network.topology must end with 2 outputs (rotation and translation)",
+            ));
+        }
+
+        let obstacles = &self.obstacles;
+        for shape in &obstacles.shapes {
+            shape.check().map_err(invalid)?;
+        }
+        let max = Position {
+            x: self.arena.width as f32,
+            y: self.arena.height as f32,
+        };
+        if !obstacles::has_room(&obstacles.shapes, &max) {
+            return Err(invalid(
+                "obstacles.shapes must leave room in the arena for agents and targets",
+            ));
+        }
+        if obstacles.sense && !topology[0].is_multiple_of(2) {
+            return Err(invalid(
+                "network.topology must start with an even number of inputs when obstacles.sense is set",
+            ));
+        }
+
+        let fitness = &self.fitness;
+        let weights = [
+            ("hits", fitness.hits),
+            ("distance", fitness.distance),
+            ("energy", fitness.energy),
+            ("time_to_first_hit", fitness.time_to_first_hit),
+            ("spinning", fitness.spinning),
+            ("novelty", fitness.novelty),
+        ];
+        for (name, weight) in weights {
+            if !weight.is_finite() {
+                return Err(invalid(format!(
+                    "fitness.{name} must be finite, got {weight}"
+                )));
+            }
+        }
+        let spin = [fitness.spin_speed, fitness.spin_rotation];
+        if !spin.iter().all(|v| *v >= 0.0 && v.is_finite()) {
+            return Err(invalid(
+                "fitness.spin_speed and fitness.spin_rotation must be at least 0",
+            ));
+        }
+
+        if self.evaluation.episodes == 0 {
+            return Err(invalid("evaluation.episodes must be greater than 0"));
+        }
+
+        let novelty = &self.novelty;
+        if novelty.k == 0 {
+            return Err(invalid("novelty.k must be greater than 0"));
+        }
+        let ticks = self.reset_interval();
+        if novelty.samples == 0 || novelty.samples as u64 > ticks {
+            return Err(invalid(format!(
+                "novelty.samples must be within [1, {ticks}], the ticks in a generation, got {}",
+                novelty.samples
+            )));
+        }
+        if novelty.bins == 0 {
+            return Err(invalid("novelty.bins must be greater than 0"));
+        }
+
+        let selection = &self.selection;
+        if selection.tournament_size == 0 {
+            return Err(invalid("selection.tournament_size must be greater than 0"));
+        }
+        let probability = selection.tournament_probability;
+        if !(probability > 0.0 && probability <= 1.0) {
+            return Err(invalid(format!(
+                "selection.tournament_probability must be within (0, 1], got {probability}"
+            )));
+        }
+        let fraction = selection.truncation_fraction;
+        if !(fraction > 0.0 && fraction <= 1.0) {
+            return Err(invalid(format!(
+                "selection.truncation_fraction must be within (0, 1], got {fraction}"
+            )));
+        }
+        let temperature = selection.temperature;
+        if !(temperature > 0.0 && temperature.is_finite()) {
+            return Err(invalid(format!(
+                "selection.temperature must be greater than 0, got {temperature}"
+            )));
+        }
+        let objectives = &selection.objectives;
+        if objectives.is_empty() {
+            return Err(invalid("selection.objectives must not be empty"));
+        }
+        if let Some(term) = objectives
+            .iter()
+            .enumerate()
+            .find(|&(i, term)| objectives[..i].contains(term))
+        {
+            return Err(invalid(format!(
+                "selection.objectives has {:?} more than once",
+                term.1
+            )));
+        }
+        if self.selection.elites >= sim.num_agents {
+            return Err(invalid(format!(
+                "selection.elites must be less than simulation.num_agents ({}), got {}",
+                sim.num_agents, self.selection.elites
+            )));
+        }
+
+        let hall_of_fame = &self.hall_of_fame;
+        if hall_of_fame.reinject > 0 && hall_of_fame.size == 0 {
+            return Err(invalid(
+                "hall_of_fame.reinject needs a hall of fame, set hall_of_fame.size",
+            ));
+        }
+        if self.selection.elites + hall_of_fame.reinject > sim.num_agents {
+            return Err(invalid(format!(
+                "selection.elites and hall_of_fame.reinject must add up to at most simulation.num_agents ({})",
+                sim.num_agents
+            )));
+        }
+
+        let islands = &self.islands;
+        if islands.count == 0 {
+            return Err(invalid("islands.count must be greater than 0"));
+        }
+        let immigrants = islands.migrants * islands.neighbours();
+        if self.selection.elites + hall_of_fame.reinject + immigrants > sim.num_agents {
+            return Err(invalid(format!(
+                "selection.elites, hall_of_fame.reinject and the {immigrants} migrants each island takes in must add up to at most simulation.num_agents ({})",
+                sim.num_agents
+            )));
+        }
+
+        let steady_state = &self.steady_state;
+        if steady_state.lifetime_seconds == 0
+            || steady_state.window == 0
+            || steady_state.report_seconds == 0
+        {
+            return Err(invalid(
+                "steady_state.lifetime_seconds, window and report_seconds must be greater than 0",
+            ));
+        }
+        if steady_state.enabled && (islands.count > 1 || self.evaluation.episodes > 1) {
+            return Err(invalid(
+                "steady_state can't be combined with more than one island or evaluation episode",
+            ));
+        }
+        // Stats are exported a generation at a time, which steady-state runs don't have
+        if steady_state.enabled && (self.stats.csv.is_some() || self.stats.jsonl.is_some()) {
+            return Err(invalid(
+                "stats.csv and stats.jsonl can't be combined with steady_state",
+            ));
+        }
+        // Islands are kept in step by generations of the same length, and steady-state runs have no episodes
+        // to bring targets back
+        if !self.simulation.respawn_targets && (islands.count > 1 || steady_state.enabled) {
+            return Err(invalid(
+                "simulation.respawn_targets = false can't be combined with more than one island or steady_state",
+            ));
+        }
+
+        let crossover = &self.crossover;
+        if crossover.points == 0 {
+            return Err(invalid("crossover.points must be greater than 0"));
+        }
+        if !(crossover.alpha >= 0.0 && crossover.alpha.is_finite()) {
+            return Err(invalid(format!(
+                "crossover.alpha must be at least 0, got {}",
+                crossover.alpha
+            )));
+        }
+        if !(crossover.eta >= 0.0 && crossover.eta.is_finite()) {
+            return Err(invalid(format!(
+                "crossover.eta must be at least 0, got {}",
+                crossover.eta
+            )));
+        }
+
+        let probability = self.mutation.probability;
+        if !(0.0..=1.0).contains(&probability) {
+            return Err(invalid(format!(
+                "mutation.probability must be within [0, 1], got {probability}"
+            )));
+        }
+        let sigma = self.mutation.sigma;
+        if !(sigma > 0.0 && sigma.is_finite()) {
+            return Err(invalid(format!(
+                "mutation.sigma must be greater than 0, got {sigma}"
+            )));
+        }
+        let learning_rate = self.mutation.learning_rate;
+        if !(learning_rate > 0.0 && learning_rate.is_finite()) {
+            return Err(invalid(format!(
+                "mutation.learning_rate must be greater than 0, got {learning_rate}"
+            )));
+        }
+        if let Some((min, max)) = self.mutation.bounds {
+            if !(min < max && min.is_finite() && max.is_finite()) {
+                return Err(invalid(format!(
+                    "mutation.bounds must be [min, max] with min < max, got [{min}, {max}]"
+                )));
+            }
+        }
+
+        let vision = &self.vision;
+        if !(vision.viewing_distance > 0.0 && vision.viewing_distance.is_finite()) {
+            return Err(invalid(format!(
+                "vision.viewing_distance must be greater than 0, got {}",
+                vision.viewing_distance
+            )));
+        }
+        if !(vision.vision_cone > 0.0 && vision.vision_cone <= 2.0 * PI) {
+            return Err(invalid(format!(
+                "vision.vision_cone must be within (0, 2PI], got {}",
+                vision.vision_cone
+            )));
+        }
+
+        check_range(
+            "velocity.initial_magnitude",
+            self.velocity.initial_magnitude,
+        )?;
+        check_range("velocity.reset_magnitude", self.velocity.reset_magnitude)?;
+
+        Ok(())
+    }
+
+    // Number of ticks in a generation, when none of its episodes end early
+    pub fn reset_interval(&self) -> u64 {
+        self.episode_ticks() * self.evaluation.episodes
+    }
+
+    // Most ticks in each of a generation's episodes
+    pub fn episode_ticks(&self) -> u64 {
+        self.simulation.tickrate as u64 * self.simulation.population_lifetime_seconds
+    }
+
+    // Whether novelty counts towards selection, so behaviours need tracking
+    pub fn uses_novelty(&self) -> bool {
+        self.fitness.novelty != 0.0
+            || (self.selection.method == SelectionMethod::Nsga2
+                && self.selection.objectives.contains(&FitnessTerm::Novelty))
+    }
+}
+
+impl FromStr for Config {
+    type Err = ConfigError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::from_table(s.parse()?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn default_is_valid() {
+        assert!(Config::default().validate().is_ok());
+        assert!("".parse::<Config>().is_ok());
+    }
+
+    #[test]
+    fn example_file() {
+        let config: Config = include_str!("../example.toml").parse().unwrap();
+        assert_eq!(config.network.topology, NetworkConfig::default().topology);
+    }
+
+    #[test]
+    fn partial_file() {
+        let config: Config = "[simulation]\nnum_agents = 12\n\n[network]\ntopology = [5, 2]\n"
+            .parse()
+            .unwrap();
+        assert_eq!(config.simulation.num_agents, 12);
+        assert_eq!(config.simulation.num_targets, 50);
+        assert_eq!(config.network.topology, vec![5, 2]);
+    }
+
+    #[test]
+    fn overrides() {
+        let overrides = [
+            "simulation.num_agents=12".to_owned(),
+            "network.topology=[5, 2]".to_owned(),
+            "mutation.operator=gaussian".to_owned(),
+            "crossover.operator=k_point".to_owned(),
+            "selection.method=sus".to_owned(),
+            "selection.objectives=[\"hits\", \"time_to_first_hit\"]".to_owned(),
+        ];
+        let config = Config::load_with_overrides(None::<&str>, &overrides).unwrap();
+        assert_eq!(config.simulation.num_agents, 12);
+        assert_eq!(config.network.topology, vec![5, 2]);
+        assert_eq!(config.mutation.operator, MutationOperator::Gaussian);
+        assert_eq!(config.crossover.operator, CrossoverOperator::KPoint);
+        assert_eq!(config.selection.method, SelectionMethod::Sus);
+        assert_eq!(
+            config.selection.objectives,
+            vec![FitnessTerm::Hits, FitnessTerm::TimeToFirstHit]
+        );
+
+        let overrides = ["simulation.num_agents=many".to_owned()];
+        assert!(Config::load_with_overrides(None::<&str>, &overrides).is_err());
+        let overrides = ["simulation.num_agent=12".to_owned()];
+        assert!(Config::load_with_overrides(None::<&str>, &overrides).is_err());
+    }
+
+    #[test]
+    fn unknown_key() {
+        let err = "[simulation]\nnum_agent = 12\n"
+            .parse::<Config>()
+            .unwrap_err();
+        assert!(matches!(err, ConfigError::Parse(_)));
+        assert!(err.to_string().contains("num_agent"));
+    }
+
+    #[test]
+    fn out_of_range() {
+        let err = "[mutation]\nprobability = 1.5\n"
+            .parse::<Config>()
+            .unwrap_err();
+        assert!(matches!(err, ConfigError::Invalid(_)));
+
+        let err = "[velocity]\nreset_magnitude = [10.0, 5.0]\n"
+            .parse::<Config>()
+            .unwrap_err();
+        assert!(matches!(err, ConfigError::Invalid(_)));
+
+        let err = "[network]\ntopology = [9, 15, 3]\n"
+            .parse::<Config>()
+            .unwrap_err();
+        assert!(matches!(err, ConfigError::Invalid(_)));
+
+        let err = "[steady_state]\nenabled = true\n\n[stats]\ncsv = \"stats.csv\"\n"
+            .parse::<Config>()
+            .unwrap_err();
+        assert!(matches!(err, ConfigError::Invalid(_)));
+    }
+}
@@ -0,0 +1,148 @@
+use crate::components::{EpisodeResults, Performance};
+use crate::config::{Aggregate, FitnessConfig, FitnessTerm};
+use crate::stats::Summary;
+use serde::Serialize;
+
+// Each of the terms that make up an agent's fitness, before they're weighted
+// Hits are only fractional once episodes are aggregated
+#[derive(Clone, Debug, Default, PartialEq, Serialize)]
+pub struct FitnessTerms {
+    pub hits: f32,
+    pub distance: f32,
+    pub energy: f32,
+    pub time_to_first_hit: f32,
+    pub spinning: f32,
+    pub novelty: f32,
+}
+
+impl FitnessTerms {
+    // `generation_seconds` stands in for the time to the first hit when there wasn't one
+    pub fn new(hits: u32, performance: &Performance, generation_seconds: f32) -> Self {
+        Self {
+            hits: hits as f32,
+            distance: performance.distance,
+            energy: performance.energy,
+            time_to_first_hit: performance.first_hit.unwrap_or(generation_seconds),
+            spinning: performance.spinning,
+            novelty: performance.novelty,
+        }
+    }
+
+    pub fn term(&self, term: FitnessTerm) -> f32 {
+        match term {
+            FitnessTerm::Hits => self.hits,
+            FitnessTerm::Distance => self.distance,
+            FitnessTerm::Energy => self.energy,
+            FitnessTerm::TimeToFirstHit => self.time_to_first_hit,
+            FitnessTerm::Spinning => self.spinning,
+            FitnessTerm::Novelty => self.novelty,
+        }
+    }
+
+    fn term_mut(&mut self, term: FitnessTerm) -> &mut f32 {
+        match term {
+            FitnessTerm::Hits => &mut self.hits,
+            FitnessTerm::Distance => &mut self.distance,
+            FitnessTerm::Energy => &mut self.energy,
+            FitnessTerm::TimeToFirstHit => &mut self.time_to_first_hit,
+            FitnessTerm::Spinning => &mut self.spinning,
+            FitnessTerm::Novelty => &mut self.novelty,
+        }
+    }
+
+    // Each term aggregated across episodes on its own, so the min is the worst of each whichever way it's better
+    pub fn aggregate(episodes: &[FitnessTerms], aggregate: Aggregate) -> Self {
+        let mut terms = Self::default();
+        for term in FitnessTerm::ALL {
+            let sign = if term.maximised() { 1.0 } else { -1.0 };
+            let values: Vec<f32> = episodes.iter().map(|e| sign * e.term(term)).collect();
+            let summary = Summary::from_values(&values);
+            let value = match aggregate {
+                Aggregate::Mean => summary.mean,
+                Aggregate::Min => summary.min,
+                Aggregate::Median => summary.median,
+            };
+            *terms.term_mut(term) = sign * value;
+        }
+        terms
+    }
+
+    // An agent's terms for a whole generation, its episodes aggregated along with the novelty of its behaviour over all of them
+    pub fn for_generation(
+        results: &EpisodeResults,
+        performance: &Performance,
+        aggregate: Aggregate,
+    ) -> Self {
+        Self {
+            novelty: performance.novelty,
+            ..Self::aggregate(&results.episodes, aggregate)
+        }
+    }
+
+    // The terms as objectives that are all better the higher they are
+    pub fn objectives(&self, terms: &[FitnessTerm]) -> Vec<f32> {
+        terms
+            .iter()
+            .map(|&t| {
+                if t.maximised() {
+                    self.term(t)
+                } else {
+                    -self.term(t)
+                }
+            })
+            .collect()
+    }
+
+    // The weighted sum of the terms
+    pub fn fitness(&self, weights: &FitnessConfig) -> f32 {
+        self.hits * weights.hits
+            + self.distance * weights.distance
+            + self.energy * weights.energy
+            + self.time_to_first_hit * weights.time_to_first_hit
+            + self.spinning * weights.spinning
+            + self.novelty * weights.novelty
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn weighted_sum() {
+        let performance = Performance {
+            distance: 100.0,
+            energy: 20.0,
+            first_hit: None,
+            spinning: 2.0,
+            novelty: 4.0,
+        };
+        let terms = FitnessTerms::new(3, &performance, 60.0);
+        assert_eq!(terms.time_to_first_hit, 60.0);
+
+        // The default only counts hits
+        assert_eq!(terms.fitness(&FitnessConfig::default()), 3.0);
+
+        let weights = FitnessConfig {
+            hits: 10.0,
+            distance: 0.01,
+            energy: -0.5,
+            time_to_first_hit: -0.1,
+            spinning: -1.0,
+            novelty: 0.5,
+            ..FitnessConfig::default()
+        };
+        assert!((terms.fitness(&weights) - (30.0 + 1.0 - 10.0 - 6.0 - 2.0 + 2.0)).abs() < 1e-5);
+
+        // The worst of each term, the most energy but the fewest hits
+        let other = FitnessTerms {
+            hits: 1.0,
+            energy: 10.0,
+            ..terms.clone()
+        };
+        let worst = FitnessTerms::aggregate(&[terms.clone(), other.clone()], Aggregate::Min);
+        assert_eq!((worst.hits, worst.energy), (1.0, 20.0));
+        let mean = FitnessTerms::aggregate(&[terms, other], Aggregate::Mean);
+        assert_eq!((mean.hits, mean.energy), (2.0, 15.0));
+    }
+}
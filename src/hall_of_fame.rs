@@ -0,0 +1,183 @@
+use crate::neural::io::{check_binary_header, read_f32, read_u32, read_u64, save_atomically};
+use crate::neural::Network;
+use std::fs::File;
+use std::io::{self, BufReader, Read, Write};
+use std::path::Path;
+
+// The best networks seen over a whole run, kept so that they outlive the generation they were in
+//
+// Binary, version 1, little-endian:
+//
+//   4 bytes   magic, "GHOF"
+//   u32       format version
+//   u32       capacity
+//   u32       number of members, best first, followed by each member's
+//             u64 generation, u32 score, f32 fitness, u64 seed and network (see neural::io)
+//
+// Checkpoints hold the same capacity and members after their agents
+
+const MAGIC: &[u8; 4] = b"GHOF";
+const VERSION: u32 = 1;
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Member {
+    // the generation it was ranked in
+    pub generation: u64,
+    // targets hit over the generation's episodes
+    pub score: u32,
+    // the weighted sum of its fitness terms, which members are ranked by
+    pub fitness: f32,
+    // seed of the run it came from
+    pub seed: u64,
+    pub network: Network,
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct HallOfFame {
+    capacity: usize,
+    // best first
+    members: Vec<Member>,
+}
+
+impl HallOfFame {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            members: vec![],
+        }
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    pub fn members(&self) -> &[Member] {
+        &self.members
+    }
+
+    // Keep the candidate if it's among the best seen, ties going to the members that got there first
+    // A network already in the hall (such as an elite carried over) only has its entry kept at its best
+    pub fn consider(&mut self, candidate: Member) {
+        if let Some(i) = self
+            .members
+            .iter()
+            .position(|m| m.network == candidate.network)
+        {
+            if candidate.fitness <= self.members[i].fitness {
+                return;
+            }
+            self.members.remove(i);
+        }
+
+        let position = self
+            .members
+            .iter()
+            .position(|m| candidate.fitness > m.fitness)
+            .unwrap_or(self.members.len());
+        if position < self.capacity {
+            self.members.insert(position, candidate);
+            self.members.truncate(self.capacity);
+        }
+    }
+
+    // Capacity and members, without a header, for embedding in other files
+    pub fn write_members<W: Write + ?Sized>(&self, w: &mut W) -> io::Result<()> {
+        w.write_all(&(self.capacity as u32).to_le_bytes())?;
+        w.write_all(&(self.members.len() as u32).to_le_bytes())?;
+        for m in &self.members {
+            w.write_all(&m.generation.to_le_bytes())?;
+            w.write_all(&m.score.to_le_bytes())?;
+            w.write_all(&m.fitness.to_le_bytes())?;
+            w.write_all(&m.seed.to_le_bytes())?;
+            m.network.write_binary(w)?;
+        }
+        Ok(())
+    }
+
+    pub fn read_members<R: Read + ?Sized>(r: &mut R) -> io::Result<Self> {
+        let capacity = read_u32(r)? as usize;
+        let count = read_u32(r)?;
+        let members = (0..count)
+            .map(|_| {
+                Ok(Member {
+                    generation: read_u64(r)?,
+                    score: read_u32(r)?,
+                    fitness: read_f32(r)?,
+                    seed: read_u64(r)?,
+                    network: Network::read_binary(r)?,
+                })
+            })
+            .collect::<io::Result<Vec<Member>>>()?;
+        Ok(Self { capacity, members })
+    }
+
+    pub fn write<W: Write + ?Sized>(&self, w: &mut W) -> io::Result<()> {
+        w.write_all(MAGIC)?;
+        w.write_all(&VERSION.to_le_bytes())?;
+        self.write_members(w)
+    }
+
+    pub fn read<R: Read + ?Sized>(r: &mut R) -> io::Result<Self> {
+        check_binary_header(r, MAGIC, VERSION)?;
+        Self::read_members(r)
+    }
+}
+
+pub fn save<P: AsRef<Path>>(path: P, hall_of_fame: &HallOfFame) -> io::Result<()> {
+    save_atomically(path, |w| hall_of_fame.write(w))
+}
+
+pub fn load<P: AsRef<Path>>(path: P) -> io::Result<HallOfFame> {
+    HallOfFame::read(&mut BufReader::new(File::open(path)?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand_pcg::Pcg64Mcg;
+
+    #[test]
+    fn keeps_the_best() {
+        let mut rng = Pcg64Mcg::new(0xcafef00dd15ea5e5);
+        let mut member = |generation, fitness| Member {
+            generation,
+            score: fitness as u32,
+            fitness,
+            seed: 3,
+            network: Network::random(&mut rng, &[3, 2]),
+        };
+        let (a, b, c, d) = (
+            member(0, 2.0),
+            member(0, 5.0),
+            member(1, 2.0),
+            member(1, 4.0),
+        );
+
+        let mut hall = HallOfFame::new(3);
+        for m in [&a, &b, &c, &d] {
+            hall.consider(m.clone());
+        }
+        assert_eq!(hall.members(), &[b.clone(), d.clone(), a.clone()]);
+
+        // The same network again only moves up if it did better
+        hall.consider(Member {
+            generation: 2,
+            fitness: 1.0,
+            ..d.clone()
+        });
+        assert_eq!(hall.members()[1], d);
+        let d_better = Member {
+            generation: 2,
+            fitness: 6.0,
+            ..d.clone()
+        };
+        hall.consider(d_better.clone());
+        assert_eq!(hall.members(), &[d_better, b, a]);
+
+        let mut buf = vec![];
+        hall.write(&mut buf).unwrap();
+        let read = HallOfFame::read(&mut buf.as_slice()).unwrap();
+        assert_eq!(read.capacity(), 3);
+        assert_eq!(read.members(), hall.members());
+    }
+}
@@ -0,0 +1,310 @@
+use crate::components::{Agent, Elite};
+use crate::config::{Config, MigrationTopology};
+use crate::neural::Network;
+use crate::resources::{GenerationEvent, Island, WorldRng};
+use crate::simulation::{Simulation, SimulationBuilder, StopAfter};
+use crate::stats::GenerationStats;
+use crate::systems::export_stats::ExportStats;
+use rand::seq::SliceRandom;
+use specs::{prelude::*, World};
+use std::ffi::OsString;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::thread::{self, JoinHandle};
+use std::time::Instant;
+
+// Several populations evolved side by side, each on its own thread in its own world with its own targets
+// They only meet at the end of a generation, to report their stats and, every `islands.migration_interval`
+// generations, to send copies of their fittest agents to the islands they're connected to
+pub struct Archipelago {
+    islands: Vec<IslandThread>,
+    topology: MigrationTopology,
+    print: bool,
+    ticks: u64,
+    generation: u64,
+}
+
+// What the archipelago asks of an island's thread
+enum Command {
+    // run to the end of the generation, or to the tick given if that comes first
+    RunGeneration(Option<u64>),
+    Immigrate(Vec<Network>),
+}
+
+// What an island's thread sends back once it has run as far as it was asked to
+struct Report {
+    ticks: u64,
+    // whether the island stopped at the end of its generation rather than at the tick it was given
+    ended: bool,
+    // hits and emigrants of the generation that just ended, both empty mid-generation
+    scores: Vec<u32>,
+    emigrants: Vec<Network>,
+}
+
+// The channels to and from an island's thread, which hands back its world once the commands stop
+struct IslandThread {
+    commands: Sender<Command>,
+    reports: Receiver<Report>,
+    thread: JoinHandle<World>,
+}
+
+// `path` with the island's index added before its extension, e.g. stats.csv to stats.island-1.csv
+pub fn island_path(path: &Path, index: usize) -> PathBuf {
+    let mut name = OsString::from(path.file_stem().unwrap_or_default());
+    name.push(format!(".island-{index}"));
+    if let Some(extension) = path.extension() {
+        name.push(".");
+        name.push(extension);
+    }
+    path.with_file_name(name)
+}
+
+// An island's own config, seeded `seed + index` and writing its files to paths of its own
+fn island_config(config: &Config, seed: u64, index: usize) -> Config {
+    let mut config = config.clone();
+    config.simulation.seed = Some(seed.wrapping_add(index as u64));
+    // The archipelago prints every island's line together once they've all finished the generation
+    config.stats.print = false;
+    for path in [
+        &mut config.stats.csv,
+        &mut config.stats.jsonl,
+        &mut config.checkpoint.path,
+        &mut config.hall_of_fame.path,
+    ]
+    .into_iter()
+    .flatten()
+    {
+        *path = island_path(path, index);
+    }
+    config
+}
+
+// Copy the networks over randomly picked agents that aren't elites
+fn immigrate(world: &mut World, networks: Vec<Network>) {
+    let (entities, mut agents, elites, mut rng) = world.system_data::<(
+        Entities,
+        WriteStorage<Agent>,
+        ReadStorage<Elite>,
+        WriteExpect<WorldRng>,
+    )>();
+    let candidates: Vec<Entity> = (&entities, &agents, !&elites)
+        .join()
+        .map(|(entity, _, _)| entity)
+        .collect();
+    let replaced = candidates.choose_multiple(&mut rng.0, networks.len());
+    for (&entity, network) in replaced.zip(networks) {
+        agents.get_mut(entity).unwrap().network = network;
+    }
+}
+
+// An island's thread, which builds its simulation itself as dispatchers can't move between threads
+fn run_island(
+    builder: SimulationBuilder,
+    index: usize,
+    commands: Receiver<Command>,
+    reports: Sender<Report>,
+) -> World {
+    let mut simulation = builder.build();
+    simulation.world.insert(Island::new(index));
+
+    for command in commands {
+        match command {
+            Command::RunGeneration(last_tick) => {
+                let mut ended = false;
+                while !ended && last_tick.is_none_or(|last_tick| simulation.ticks() < last_tick) {
+                    if let Err(e) = simulation.step() {
+                        eprintln!("Unable to save checkpoint: {e}");
+                    }
+                    ended = simulation
+                        .generation()
+                        .ended(GenerationEvent::GenerationEnded);
+                }
+                let ticks = simulation.ticks();
+                let mut island = simulation.world.write_resource::<Island>();
+                let report = Report {
+                    ticks,
+                    ended,
+                    scores: std::mem::take(&mut island.scores),
+                    emigrants: std::mem::take(&mut island.emigrants),
+                };
+                reports
+                    .send(report)
+                    .expect("Unable to report to the archipelago");
+            }
+            Command::Immigrate(networks) => immigrate(&mut simulation.world, networks),
+        }
+    }
+    simulation.world
+}
+
+impl Archipelago {
+    // `islands.count` islands, each exporting stats if the config asks for them
+    pub fn new(config: &Config, seed: u64) -> io::Result<Self> {
+        let islands = (0..config.islands.count)
+            .map(|index| {
+                let config = island_config(config, seed, index);
+                let mut builder =
+                    Simulation::builder(&config).seed(config.simulation.seed.unwrap());
+                if let Some(export_stats) = ExportStats::from_config(&config.stats)? {
+                    builder = builder.export_stats(export_stats);
+                }
+
+                let (commands, command_receiver) = mpsc::channel();
+                let (report_sender, reports) = mpsc::channel();
+                let thread = thread::Builder::new()
+                    .name(format!("island-{index}"))
+                    .spawn(move || run_island(builder, index, command_receiver, report_sender))?;
+                Ok(IslandThread {
+                    commands,
+                    reports,
+                    thread,
+                })
+            })
+            .collect::<io::Result<Vec<IslandThread>>>()?;
+
+        Ok(Self {
+            islands,
+            topology: config.islands.topology,
+            print: config.stats.print,
+            ticks: 0,
+            generation: 0,
+        })
+    }
+
+    // The furthest any island has run
+    pub fn ticks(&self) -> u64 {
+        self.ticks
+    }
+
+    // Run every island to the end of its generation, or to `last_tick` if that comes first
+    // The generation only ends for the archipelago once it has ended on every island
+    fn run_generation(&mut self, last_tick: Option<u64>) {
+        for island in &self.islands {
+            island
+                .commands
+                .send(Command::RunGeneration(last_tick))
+                .expect("Island thread stopped");
+        }
+        let reports: Vec<Report> = self
+            .islands
+            .iter()
+            .map(|island| island.reports.recv().expect("Island thread stopped"))
+            .collect();
+        self.ticks = reports.iter().map(|r| r.ticks).max().unwrap_or(self.ticks);
+
+        if reports.iter().all(|r| r.ended) {
+            self.generation += 1;
+            self.end_generation(self.generation, reports);
+        }
+    }
+
+    fn end_generation(&mut self, generation: u64, reports: Vec<Report>) {
+        if self.print {
+            for (index, report) in reports.iter().enumerate() {
+                let stats = GenerationStats::from_scores(&report.scores);
+                println!(
+                    "Island {} Gen {} = Avg: {:.2} - Min: {} - Max: {} - Total: {}",
+                    index, generation, stats.avg, stats.min, stats.max, stats.total
+                );
+            }
+            let all_scores: Vec<u32> = reports.iter().flat_map(|r| r.scores.clone()).collect();
+            let stats = GenerationStats::from_scores(&all_scores);
+            println!(
+                "Gen {} = Avg: {:.2} - Min: {} - Max: {} - Total: {} (all islands)",
+                generation, stats.avg, stats.min, stats.max, stats.total
+            );
+        }
+
+        // Emigrants are only sent on the generations that migrate (see Emigrate)
+        if reports.iter().all(|r| r.emigrants.is_empty()) {
+            return;
+        }
+        let count = self.islands.len();
+        for (index, island) in self.islands.iter().enumerate() {
+            let immigrants: Vec<Network> = match self.topology {
+                MigrationTopology::Ring => reports[(index + count - 1) % count].emigrants.clone(),
+                MigrationTopology::FullyConnected => (0..count)
+                    .filter(|&from| from != index)
+                    .flat_map(|from| reports[from].emigrants.iter().cloned())
+                    .collect(),
+            };
+            island
+                .commands
+                .send(Command::Immigrate(immigrants))
+                .expect("Island thread stopped");
+        }
+    }
+
+    // Run ticks back to back with no rendering until `stop` is reached, like Simulation::run_headless
+    pub fn run_headless(&mut self, stop: StopAfter) {
+        let start = Instant::now();
+        let start_tick = self.ticks;
+
+        match stop {
+            StopAfter::Generations(generations) => {
+                while self.generation < generations {
+                    self.run_generation(None);
+                }
+            }
+            StopAfter::Ticks(last_tick) => {
+                while self.ticks < last_tick {
+                    self.run_generation(Some(last_tick));
+                }
+            }
+        }
+
+        let elapsed = start.elapsed().as_secs_f32();
+        let elapsed_ticks = self.ticks - start_tick;
+        println!(
+            "Seconds: {elapsed:.2} - Ticks: {elapsed_ticks} per island - Tickrate: {:.2} per island",
+            elapsed_ticks as f32 / elapsed
+        );
+    }
+
+    // Stop every island's thread, handing back their worlds in island order
+    pub fn finish(self) -> Vec<World> {
+        self.islands
+            .into_iter()
+            .map(|island| {
+                drop(island.commands);
+                island.thread.join().expect("Island thread panicked")
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::simulation;
+
+    #[test]
+    fn fittest_migrate_around_the_ring() {
+        let config: Config = "[simulation]\ntickrate = 30\npopulation_lifetime_seconds = 1\nnum_agents = 10\n\n[selection]\nelites = 2\n\n[islands]\ncount = 3\nmigration_interval = 1\nmigrants = 2\n\n[stats]\nprint = false\n"
+            .parse()
+            .unwrap();
+        let mut archipelago = Archipelago::new(&config, 4).unwrap();
+        archipelago.run_headless(StopAfter::Generations(1));
+        assert_eq!(archipelago.ticks(), 30);
+
+        let worlds = archipelago.finish();
+        assert_eq!(worlds.len(), 3);
+
+        // The migrants are each island's elites, which carried over unchanged and so are still there to compare
+        for (index, world) in worlds.iter().enumerate() {
+            let (agents, elites) = world.system_data::<(ReadStorage<Agent>, ReadStorage<Elite>)>();
+            assert_eq!(elites.join().count(), 2);
+            let next = simulation::networks(&worlds[(index + 1) % 3]);
+            for (agent, _) in (&agents, &elites).join() {
+                assert!(next.contains(&agent.network));
+            }
+        }
+
+        assert_eq!(
+            island_path(Path::new("out/stats.csv"), 1),
+            Path::new("out/stats.island-1.csv")
+        );
+        assert_eq!(island_path(Path::new("run"), 0), Path::new("run.island-0"));
+    }
+}
@@ -0,0 +1,22 @@
+// The simulation, networks and genetic algorithm, without any front-end
+// The `genetic` binary adds a command line and an SDL window on top of this
+
+pub mod checkpoint;
+pub mod components;
+pub mod config;
+pub mod fitness;
+pub mod hall_of_fame;
+pub mod islands;
+pub mod neural;
+pub mod novelty;
+pub mod obstacles;
+pub mod pareto;
+pub mod population;
+pub mod recording;
+pub mod resources;
+pub mod selection;
+pub mod simulation;
+pub mod stats;
+pub mod systems;
+
+pub use simulation::{Simulation, SimulationBuilder};
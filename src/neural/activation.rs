@@ -0,0 +1,142 @@
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::str::FromStr;
+
+// The function a layer's neurons apply to their weighted input plus bias
+// Discriminants are the ids used in the binary network format, so they must never change
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Activation {
+    #[default]
+    Relu = 0,
+    LeakyRelu = 1,
+    Sigmoid = 2,
+    Tanh = 3,
+    Identity = 4,
+    Softsign = 5,
+    Step = 6,
+}
+
+const ALL: [Activation; 7] = [
+    Activation::Relu,
+    Activation::LeakyRelu,
+    Activation::Sigmoid,
+    Activation::Tanh,
+    Activation::Identity,
+    Activation::Softsign,
+    Activation::Step,
+];
+
+impl Activation {
+    #[inline]
+    pub fn apply(self, x: f32) -> f32 {
+        match self {
+            Activation::Relu => x.max(0.0),
+            Activation::LeakyRelu => {
+                if x > 0.0 {
+                    x
+                } else {
+                    0.01 * x
+                }
+            }
+            Activation::Sigmoid => 1.0 / (1.0 + (-x).exp()),
+            Activation::Tanh => x.tanh(),
+            Activation::Identity => x,
+            Activation::Softsign => x / (1.0 + x.abs()),
+            Activation::Step => {
+                if x > 0.0 {
+                    1.0
+                } else {
+                    0.0
+                }
+            }
+        }
+    }
+
+    // Apply to every value, choosing the function once rather than per value
+    pub fn apply_all(self, values: &mut [f32]) {
+        fn each(values: &mut [f32], activation: Activation) {
+            for v in values {
+                *v = activation.apply(*v);
+            }
+        }
+
+        match self {
+            Activation::Relu => each(values, Activation::Relu),
+            Activation::LeakyRelu => each(values, Activation::LeakyRelu),
+            Activation::Sigmoid => each(values, Activation::Sigmoid),
+            Activation::Tanh => each(values, Activation::Tanh),
+            Activation::Identity => each(values, Activation::Identity),
+            Activation::Softsign => each(values, Activation::Softsign),
+            Activation::Step => each(values, Activation::Step),
+        }
+    }
+
+    // The range of outputs that is treated as meaningful when outputs are mapped onto something else
+    // For bounded functions this is their whole range, unbounded ones are clamped to it
+    pub fn range(self) -> (f32, f32) {
+        match self {
+            Activation::Relu | Activation::LeakyRelu | Activation::Sigmoid | Activation::Step => {
+                (0.0, 1.0)
+            }
+            Activation::Tanh | Activation::Identity | Activation::Softsign => (-1.0, 1.0),
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Activation::Relu => "relu",
+            Activation::LeakyRelu => "leaky_relu",
+            Activation::Sigmoid => "sigmoid",
+            Activation::Tanh => "tanh",
+            Activation::Identity => "identity",
+            Activation::Softsign => "softsign",
+            Activation::Step => "step",
+        }
+    }
+
+    pub fn id(self) -> u32 {
+        self as u32
+    }
+
+    pub fn from_id(id: u32) -> Option<Self> {
+        ALL.into_iter().find(|a| a.id() == id)
+    }
+}
+
+impl fmt::Display for Activation {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl FromStr for Activation {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        ALL.into_iter()
+            .find(|a| a.name() == s)
+            .ok_or_else(|| format!("unknown activation `{s}`"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn outputs_within_range() {
+        for activation in ALL {
+            let (min, max) = activation.range();
+            assert_eq!(activation.name().parse(), Ok(activation));
+            assert_eq!(Activation::from_id(activation.id()), Some(activation));
+            for x in [-100.0, -1.0, -0.1, 0.0, 0.1, 1.0] {
+                let y = activation.apply(x);
+                assert!(y <= max, "{activation}({x}) = {y}");
+                if !matches!(activation, Activation::LeakyRelu | Activation::Identity) {
+                    assert!(y >= min, "{activation}({x}) = {y}");
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,360 @@
+use super::{Activation, Layer, Network, Neuron};
+use std::fs::{self, File};
+use std::io::{self, BufRead, BufWriter, Read, Write};
+use std::path::Path;
+
+// Networks can be stored in a human-readable text form or a compact binary form
+// Both keep the neurons per layer (including the input layer), the activation of every layer but the input layer,
+// and then every neuron from the second layer on, each as its bias followed by its input weights
+//
+// Text, version 1:
+//
+//   genetic-network 1
+//   layers 2 3 1
+//   activations relu tanh
+//   0.6 0.4 0.6
+//   0.8 0.2 0.5
+//   -0.4 0.7 0.3
+//   0.5 0.3 0.4 0.5
+//
+// f32s are written with their shortest round-tripping representation, so weights are kept exactly
+//
+// Binary, version 1, all values little-endian:
+//
+//   4 bytes      magic, "GNET"
+//   u32          format version
+//   u32          number of layers (n)
+//   n x u32      neurons per layer
+//   n-1 x u32    activation ids (see Activation)
+//   f32s         bias and then input weights of every neuron
+//
+// Readers reject unknown versions and any file whose neurons don't match the declared layer sizes
+
+pub const TEXT_HEADER: &str = "genetic-network";
+pub const BINARY_MAGIC: &[u8; 4] = b"GNET";
+pub const VERSION: u32 = 1;
+
+pub fn invalid_data(msg: impl Into<String>) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, msg.into())
+}
+
+pub fn read_line<R: BufRead + ?Sized>(r: &mut R) -> io::Result<String> {
+    let mut line = String::new();
+    if r.read_line(&mut line)? == 0 {
+        return Err(io::Error::new(
+            io::ErrorKind::UnexpectedEof,
+            "unexpected end of file",
+        ));
+    }
+    Ok(line)
+}
+
+pub fn read_u32<R: Read + ?Sized>(r: &mut R) -> io::Result<u32> {
+    let mut buf = [0; 4];
+    r.read_exact(&mut buf)?;
+    Ok(u32::from_le_bytes(buf))
+}
+
+pub fn read_u64<R: Read + ?Sized>(r: &mut R) -> io::Result<u64> {
+    let mut buf = [0; 8];
+    r.read_exact(&mut buf)?;
+    Ok(u64::from_le_bytes(buf))
+}
+
+pub fn read_f32<R: Read + ?Sized>(r: &mut R) -> io::Result<f32> {
+    let mut buf = [0; 4];
+    r.read_exact(&mut buf)?;
+    Ok(f32::from_le_bytes(buf))
+}
+
+// The files of this crate (networks, populations, the hall of fame and checkpoints) each have a single version
+// Until a format is released it's changed in place rather than given a new version, so there are no older
+// versions to read, and the version is only checked to turn away files from a newer build
+
+// Parse a `<header> <version>` line, rejecting any version but `version`
+pub fn check_text_header(line: &str, header: &str, version: u32) -> io::Result<()> {
+    let found = line
+        .strip_prefix(header)
+        .and_then(|v| v.trim().parse::<u32>().ok())
+        .ok_or_else(|| invalid_data(format!("expected `{header} <version>` header")))?;
+    if found != version {
+        return Err(invalid_data(format!(
+            "unsupported {header} version {found}"
+        )));
+    }
+    Ok(())
+}
+
+// Read a magic and version, rejecting any version but `version`
+pub fn check_binary_header<R: Read + ?Sized>(
+    r: &mut R,
+    magic: &[u8; 4],
+    version: u32,
+) -> io::Result<()> {
+    let mut found = [0; 4];
+    r.read_exact(&mut found)?;
+    if &found != magic {
+        return Err(invalid_data(format!(
+            "expected {} magic",
+            String::from_utf8_lossy(magic)
+        )));
+    }
+    let found = read_u32(r)?;
+    if found != version {
+        return Err(invalid_data(format!(
+            "unsupported {} version {found}",
+            String::from_utf8_lossy(magic)
+        )));
+    }
+    Ok(())
+}
+
+// Write to a temporary file first and then move it into place, so that an interrupted save never clobbers
+// the last good file
+pub fn save_atomically<P: AsRef<Path>>(
+    path: P,
+    write: impl FnOnce(&mut BufWriter<File>) -> io::Result<()>,
+) -> io::Result<()> {
+    let path = path.as_ref();
+    let mut tmp = path.as_os_str().to_owned();
+    tmp.push(".tmp");
+
+    let mut w = BufWriter::new(File::create(&tmp)?);
+    write(&mut w)?;
+    w.into_inner()?.sync_all()?;
+    fs::rename(&tmp, path)
+}
+
+fn check_sizes(sizes: &[usize]) -> io::Result<()> {
+    if sizes.len() < 2 || sizes.contains(&0) {
+        return Err(invalid_data(format!("invalid layer sizes {sizes:?}")));
+    }
+    Ok(())
+}
+
+fn check_activations(activations: &[Activation], sizes: &[usize]) -> io::Result<()> {
+    if activations.len() != sizes.len() - 1 {
+        return Err(invalid_data(format!(
+            "expected {} activations, found {}",
+            sizes.len() - 1,
+            activations.len()
+        )));
+    }
+    Ok(())
+}
+
+impl Network {
+    fn sizes(&self) -> Vec<usize> {
+        std::iter::once(self.input_size())
+            .chain(self.layers.iter().map(|l| l.output_size()))
+            .collect()
+    }
+
+    pub fn write_text<W: Write + ?Sized>(&self, w: &mut W) -> io::Result<()> {
+        writeln!(w, "{TEXT_HEADER} {VERSION}")?;
+        write!(w, "layers")?;
+        for size in self.sizes() {
+            write!(w, " {size}")?;
+        }
+        writeln!(w)?;
+        write!(w, "activations")?;
+        for layer in &self.layers {
+            write!(w, " {}", layer.activation)?;
+        }
+        writeln!(w)?;
+
+        for neuron in self.layers.iter().flat_map(|l| l.neurons()) {
+            write!(w, "{}", neuron.bias)?;
+            for weight in &neuron.input_weights {
+                write!(w, " {weight}")?;
+            }
+            writeln!(w)?;
+        }
+
+        Ok(())
+    }
+
+    pub fn read_text<R: BufRead + ?Sized>(r: &mut R) -> io::Result<Self> {
+        check_text_header(&read_line(r)?, TEXT_HEADER, VERSION)?;
+
+        let sizes = read_line(r)?;
+        let sizes = sizes
+            .strip_prefix("layers")
+            .ok_or_else(|| invalid_data("expected layer sizes"))?
+            .split_whitespace()
+            .map(|s| s.parse::<usize>())
+            .collect::<Result<Vec<usize>, _>>()
+            .map_err(|e| invalid_data(format!("invalid layer size: {e}")))?;
+        check_sizes(&sizes)?;
+
+        let activations = read_line(r)?;
+        let activations = activations
+            .strip_prefix("activations")
+            .ok_or_else(|| invalid_data("expected layer activations"))?
+            .split_whitespace()
+            .map(|s| s.parse::<Activation>())
+            .collect::<Result<Vec<Activation>, _>>()
+            .map_err(invalid_data)?;
+        check_activations(&activations, &sizes)?;
+
+        let mut layers = vec![];
+        for (n, &activation) in sizes.windows(2).zip(&activations) {
+            let mut neurons = vec![];
+            for _ in 0..n[1] {
+                let values = read_line(r)?
+                    .split_whitespace()
+                    .map(|s| s.parse::<f32>())
+                    .collect::<Result<Vec<f32>, _>>()
+                    .map_err(|e| invalid_data(format!("invalid neuron value: {e}")))?;
+                if values.len() != n[0] + 1 {
+                    return Err(invalid_data(format!(
+                        "expected a bias and {} weights, found {} values",
+                        n[0],
+                        values.len()
+                    )));
+                }
+                neurons.push(Neuron::new(values[0], values[1..].to_vec()));
+            }
+            layers.push(Layer::with_activation(neurons, activation));
+        }
+
+        Ok(Network::from_layers(layers))
+    }
+
+    pub fn write_binary<W: Write + ?Sized>(&self, w: &mut W) -> io::Result<()> {
+        let sizes = self.sizes();
+        w.write_all(BINARY_MAGIC)?;
+        w.write_all(&VERSION.to_le_bytes())?;
+        w.write_all(&(sizes.len() as u32).to_le_bytes())?;
+        for size in sizes {
+            w.write_all(&(size as u32).to_le_bytes())?;
+        }
+        for layer in &self.layers {
+            w.write_all(&layer.activation.id().to_le_bytes())?;
+        }
+
+        for neuron in self.layers.iter().flat_map(|l| l.neurons()) {
+            w.write_all(&neuron.bias.to_le_bytes())?;
+            for weight in &neuron.input_weights {
+                w.write_all(&weight.to_le_bytes())?;
+            }
+        }
+
+        Ok(())
+    }
+
+    pub fn read_binary<R: Read + ?Sized>(r: &mut R) -> io::Result<Self> {
+        check_binary_header(r, BINARY_MAGIC, VERSION)?;
+
+        let num_layers = read_u32(r)?;
+        let sizes = (0..num_layers)
+            .map(|_| read_u32(r).map(|s| s as usize))
+            .collect::<io::Result<Vec<usize>>>()?;
+        check_sizes(&sizes)?;
+
+        let activations = (1..sizes.len())
+            .map(|_| {
+                let id = read_u32(r)?;
+                Activation::from_id(id)
+                    .ok_or_else(|| invalid_data(format!("unknown activation id {id}")))
+            })
+            .collect::<io::Result<Vec<Activation>>>()?;
+
+        let mut layers = vec![];
+        for (n, &activation) in sizes.windows(2).zip(&activations) {
+            let mut neurons = vec![];
+            for _ in 0..n[1] {
+                let bias = read_f32(r)?;
+                let input_weights = (0..n[0])
+                    .map(|_| read_f32(r))
+                    .collect::<io::Result<Vec<f32>>>()?;
+                neurons.push(Neuron::new(bias, input_weights));
+            }
+            layers.push(Layer::with_activation(neurons, activation));
+        }
+
+        Ok(Network::from_layers(layers))
+    }
+
+    // Read either form, detected from the first bytes
+    pub fn read<R: BufRead + ?Sized>(r: &mut R) -> io::Result<Self> {
+        if r.fill_buf()?.starts_with(BINARY_MAGIC) {
+            Self::read_binary(r)
+        } else {
+            Self::read_text(r)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand_pcg::Pcg64Mcg;
+
+    fn assert_identical(a: &Network, b: &Network) {
+        assert_eq!(a.layers.len(), b.layers.len());
+        for (a, b) in a.layers.iter().zip(&b.layers) {
+            assert_eq!(a.activation, b.activation);
+            assert_eq!(a.output_size(), b.output_size());
+            for (a, b) in a.neurons().zip(b.neurons()) {
+                assert_eq!(a.bias.to_bits(), b.bias.to_bits());
+                assert_eq!(a.input_weights, b.input_weights);
+            }
+        }
+    }
+
+    fn random_network() -> Network {
+        let mut rng = Pcg64Mcg::new(0xcafef00dd15ea5e5);
+        Network::random_with_activations(
+            &mut rng,
+            &[9, 15, 2],
+            &[Activation::LeakyRelu, Activation::Tanh],
+        )
+    }
+
+    #[test]
+    fn text_round_trip() {
+        let network = random_network();
+
+        let mut buf = vec![];
+        network.write_text(&mut buf).unwrap();
+        assert_identical(&network, &Network::read(&mut buf.as_slice()).unwrap());
+    }
+
+    #[test]
+    fn binary_round_trip() {
+        let network = random_network();
+
+        let mut buf = vec![];
+        network.write_binary(&mut buf).unwrap();
+        assert_eq!(
+            buf.len(),
+            4 + 4 + 4 + 3 * 4 + 2 * 4 + (15 * 10 + 2 * 16) * 4
+        );
+        assert_identical(&network, &Network::read(&mut buf.as_slice()).unwrap());
+    }
+
+    #[test]
+    fn text_wrong_dimensions() {
+        let text = "genetic-network 1\nlayers 2 1\nactivations relu\n0.5 0.3 0.4 0.5\n";
+        assert!(Network::read(&mut text.as_bytes()).is_err());
+    }
+
+    #[test]
+    fn binary_truncated() {
+        let mut rng = Pcg64Mcg::new(0xcafef00dd15ea5e5);
+        let mut buf = vec![];
+        Network::random(&mut rng, &[3, 2])
+            .write_binary(&mut buf)
+            .unwrap();
+        buf.pop();
+        assert!(Network::read(&mut buf.as_slice()).is_err());
+    }
+
+    #[test]
+    fn unsupported_version() {
+        let text = "genetic-network 2\nlayers 2 1\nactivations relu\n0.5 0.3 0.4\n";
+        let err = Network::read(&mut text.as_bytes()).unwrap_err();
+        assert!(err.to_string().contains("version 2"));
+    }
+}
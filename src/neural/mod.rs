@@ -1,16 +1,52 @@
-#![allow(dead_code)]
-
 use rand::{
     distributions::{Distribution, Uniform},
     Rng,
 };
-use std::f32::EPSILON;
+use rand_distr::Normal;
 use std::iter::Iterator;
 
-// A neural network where each neuron is stored as its bias and input weights
+pub mod activation;
+pub mod io;
+
+pub use activation::Activation;
+
+// A neural network where each layer is stored as a matrix of input weights (a row per neuron) and a vector of biases
 // Due to this, the first layer doesn't really exist, at least in that it doesn't have neurons with a bias.
 // Instead we input raw values into the inputs of neurons in the second layer
 
+// Sum up (input * weight) across the input synapses and add the bias
+fn weighted_sum(inputs: &[f32], weights: &[f32], bias: f32) -> f32 {
+    let input = inputs
+        .iter()
+        .zip(weights)
+        .map(|(i, w)| i * w)
+        .reduce(|a, b| a + b)
+        .unwrap();
+
+    input + bias
+}
+
+fn pick<R: Rng + ?Sized>(rng: &mut R, a: f32, b: f32) -> f32 {
+    if rng.gen::<bool>() {
+        a
+    } else {
+        b
+    }
+}
+
+// With a chance of `probability`, replace the value with the result of `mutation` on it
+fn mutate_value<R: Rng + ?Sized, F: FnMut(&mut R, f32) -> f32>(
+    rng: &mut R,
+    value: &mut f32,
+    probability: f32,
+    mutation: &mut F,
+) {
+    if rng.gen::<f32>() < probability {
+        *value = mutation(rng, *value)
+    }
+}
+
+// A single neuron, for building layers and looking at them one row at a time
 #[derive(Debug, Clone)]
 pub struct Neuron {
     bias: f32,
@@ -27,110 +63,248 @@ impl Neuron {
         }
     }
 
-    fn propagate(&self, inputs: &[f32]) -> f32 {
+    pub fn propagate(&self, inputs: &[f32], activation: Activation) -> f32 {
         assert!(inputs.len() == self.input_weights.len());
 
-        // Sum up (input * weight) across the input synapses
-        let input = inputs
-            .iter()
-            .zip(self.input_weights.iter())
-            .map(|(i, w)| i * w)
-            .reduce(|a, b| a + b)
-            .unwrap();
-
-        // Add the neuron's bias and clamp negative values to 0.
-        (input + self.bias).max(0.0)
+        activation.apply(weighted_sum(inputs, &self.input_weights, self.bias))
     }
 
-    fn crossover_uniform<R: Rng + ?Sized>(&self, rng: &mut R, b: &Self) -> Self {
+    pub fn crossover_uniform<R: Rng + ?Sized>(&self, rng: &mut R, b: &Self) -> Self {
         Self {
-            bias: if rng.gen::<bool>() { self.bias } else { b.bias },
+            bias: pick(rng, self.bias, b.bias),
             input_weights: self
                 .input_weights
                 .iter()
                 .zip(b.input_weights.iter())
-                .map(|(&a, &b)| if rng.gen::<bool>() { a } else { b })
+                .map(|(&a, &b)| pick(rng, a, b))
                 .collect(),
         }
     }
 
-    fn mutate_uniform<R: Rng + ?Sized>(
+    pub fn mutate_uniform<R: Rng + ?Sized>(
         &mut self,
-        mut rng: &mut R,
+        rng: &mut R,
         range: Uniform<f32>,
         probability: f32,
     ) {
-        if rng.gen::<f32>() < probability {
-            self.bias = range.sample(&mut rng)
-        }
+        let mut replace = |rng: &mut R, _| range.sample(rng);
+        mutate_value(rng, &mut self.bias, probability, &mut replace);
         for w in &mut self.input_weights {
-            if rng.gen::<f32>() < probability {
-                *w = range.sample(&mut rng)
-            }
+            mutate_value(rng, w, probability, &mut replace);
         }
     }
 }
 
 impl PartialEq for Neuron {
     fn eq(&self, other: &Self) -> bool {
-        (other.bias - self.bias).abs() < EPSILON
+        (other.bias - self.bias).abs() < f32::EPSILON
             && self
                 .input_weights
                 .iter()
                 .zip(&other.input_weights)
-                .all(|(a, b)| (b - a).abs() < EPSILON)
+                .all(|(a, b)| (b - a).abs() < f32::EPSILON)
     }
 }
 
 #[derive(Debug, Clone)]
 pub struct Layer {
-    neurons: Vec<Neuron>,
+    // Row-major, the input weights of each neuron one after another
+    weights: Vec<f32>,
+    biases: Vec<f32>,
+    num_inputs: usize,
+    activation: Activation,
+}
+
+// Neuron by neuron, with the same tolerance as Neuron
+impl PartialEq for Layer {
+    fn eq(&self, other: &Self) -> bool {
+        self.activation == other.activation
+            && self.num_inputs == other.num_inputs
+            && self.biases.len() == other.biases.len()
+            && self.neurons().zip(other.neurons()).all(|(a, b)| a == b)
+    }
 }
 
 impl Layer {
+    // A layer of ReLU neurons
     pub fn new(neurons: Vec<Neuron>) -> Self {
+        Self::with_activation(neurons, Activation::Relu)
+    }
+
+    pub fn with_activation(neurons: Vec<Neuron>, activation: Activation) -> Self {
         assert!(!neurons.is_empty());
         let num_weights = neurons[0].input_weights.len();
 
         assert!(neurons.iter().all(|n| n.input_weights.len() == num_weights));
 
-        Self { neurons }
+        Self {
+            weights: neurons
+                .iter()
+                .flat_map(|n| n.input_weights.iter().copied())
+                .collect(),
+            biases: neurons.iter().map(|n| n.bias).collect(),
+            num_inputs: num_weights,
+            activation,
+        }
+    }
+
+    pub fn activation(&self) -> Activation {
+        self.activation
     }
 
-    fn propagate(&self, inputs: &[f32]) -> Vec<f32> {
-        assert!(inputs.len() == self.neurons[0].input_weights.len());
+    pub fn input_size(&self) -> usize {
+        self.num_inputs
+    }
+
+    pub fn output_size(&self) -> usize {
+        self.biases.len()
+    }
+
+    // Copies of each neuron's bias and input weights
+    pub fn neurons(&self) -> impl Iterator<Item = Neuron> + '_ {
+        self.rows().map(|(bias, weights)| Neuron {
+            bias,
+            input_weights: weights.to_vec(),
+        })
+    }
+
+    // The bias and input weights of each neuron
+    fn rows(&self) -> impl Iterator<Item = (f32, &[f32])> {
+        self.biases
+            .iter()
+            .copied()
+            .zip(self.weights.chunks_exact(self.input_size()))
+    }
+
+    pub fn propagate(&self, inputs: &[f32]) -> Vec<f32> {
+        let mut outputs = vec![0.0; self.output_size()];
+        self.propagate_into(inputs, &mut outputs);
+        outputs
+    }
+
+    fn propagate_into(&self, inputs: &[f32], outputs: &mut [f32]) {
+        assert!(inputs.len() == self.input_size());
+        assert!(outputs.len() == self.output_size());
 
         // Feed the input values to each neuron in the layer and return their outputs
 
-        self.neurons.iter().map(|n| n.propagate(inputs)).collect()
+        for (output, (bias, weights)) in outputs.iter_mut().zip(self.rows()) {
+            *output = weighted_sum(inputs, weights, bias);
+        }
+        self.activation.apply_all(outputs);
+    }
+
+    // outputs = activation(weights x inputs + biases), for `batch` sets of inputs at once
+    // Both are feature-major, i.e. `inputs` holds the first input of every member of the batch, then the second, etc.
+    // which lets every member's sum for a neuron be built up side by side, each in the same order propagate uses
+    fn propagate_batch(&self, inputs: &[f32], outputs: &mut [f32], batch: usize) {
+        // A batch of one is laid out the same either way, and is quicker a neuron at a time
+        if batch == 1 {
+            return self.propagate_into(inputs, outputs);
+        }
+
+        assert_eq!(inputs.len(), self.input_size() * batch);
+        assert_eq!(outputs.len(), self.output_size() * batch);
+
+        for (outputs, (bias, weights)) in outputs.chunks_exact_mut(batch).zip(self.rows()) {
+            outputs.fill(0.0);
+            for (inputs, &weight) in inputs.chunks_exact(batch).zip(weights) {
+                for (output, &input) in outputs.iter_mut().zip(inputs) {
+                    *output += input * weight;
+                }
+            }
+            for output in outputs.iter_mut() {
+                *output += bias;
+            }
+        }
+        self.activation.apply_all(outputs);
+    }
+
+    fn assert_compatible(&self, b: &Self) {
+        assert_eq!(self.weights.len(), b.weights.len());
+        assert_eq!(self.biases.len(), b.biases.len());
+        assert_eq!(self.activation, b.activation);
     }
 
-    fn crossover_uniform<R: Rng + ?Sized>(&self, mut rng: &mut R, b: &Self) -> Self {
-        assert_eq!(self.neurons.len(), b.neurons.len());
+    fn num_genes(&self) -> usize {
+        self.weights.len() + self.biases.len()
+    }
+
+    // Combine each pair of values with `gene`
+    fn crossover<R: Rng + ?Sized, F: FnMut(&mut R, f32, f32) -> f32>(
+        &self,
+        rng: &mut R,
+        b: &Self,
+        gene: &mut F,
+    ) -> Self {
+        self.assert_compatible(b);
+
+        // Neuron by neuron, bias first, so that uniform choices match crossing over each Neuron
+        let mut weights = Vec::with_capacity(self.weights.len());
+        let mut biases = Vec::with_capacity(self.biases.len());
+        for ((a_bias, a_weights), (b_bias, b_weights)) in self.rows().zip(b.rows()) {
+            biases.push(gene(rng, a_bias, b_bias));
+            for (&a, &b) in a_weights.iter().zip(b_weights) {
+                weights.push(gene(rng, a, b));
+            }
+        }
 
         Self {
-            neurons: self
-                .neurons
-                .iter()
-                .zip(b.neurons.iter())
-                .map(|(a, b)| a.crossover_uniform(&mut rng, b))
-                .collect(),
+            weights,
+            biases,
+            num_inputs: self.num_inputs,
+            activation: self.activation,
+        }
+    }
+
+    fn crossover_neurons<R: Rng + ?Sized>(&self, rng: &mut R, b: &Self) -> Self {
+        self.assert_compatible(b);
+
+        let mut weights = Vec::with_capacity(self.weights.len());
+        let mut biases = Vec::with_capacity(self.biases.len());
+        for (a, b) in self.rows().zip(b.rows()) {
+            let (bias, row) = if rng.gen::<bool>() { a } else { b };
+            biases.push(bias);
+            weights.extend_from_slice(row);
+        }
+
+        Self {
+            weights,
+            biases,
+            num_inputs: self.num_inputs,
+            activation: self.activation,
         }
     }
 
-    fn mutate_uniform<R: Rng + ?Sized>(
+    fn mutate<R: Rng + ?Sized, F: FnMut(&mut R, f32) -> f32>(
         &mut self,
         rng: &mut R,
-        range: Uniform<f32>,
         probability: f32,
+        mutation: &mut F,
     ) {
-        for n in &mut self.neurons {
-            n.mutate_uniform(rng, range, probability);
+        let num_inputs = self.input_size();
+        for (bias, weights) in self
+            .biases
+            .iter_mut()
+            .zip(self.weights.chunks_exact_mut(num_inputs))
+        {
+            mutate_value(rng, bias, probability, mutation);
+            for w in weights {
+                mutate_value(rng, w, probability, mutation);
+            }
         }
     }
 }
 
-#[derive(Debug, Clone)]
+// Buffers for propagating through a network, kept between calls so that propagating doesn't allocate
+// once they've grown to fit the widest layer and largest batch
+#[derive(Debug, Clone, Default)]
+pub struct Workspace {
+    current: Vec<f32>,
+    next: Vec<f32>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
 pub struct Network {
     layers: Vec<Layer>,
 }
@@ -138,33 +312,51 @@ pub struct Network {
 impl Network {
     pub fn from_layers(layers: Vec<Layer>) -> Self {
         assert!(!layers.is_empty());
+        assert!(layers
+            .windows(2)
+            .all(|l| l[0].output_size() == l[1].input_size()));
 
         Self { layers }
     }
 
-    pub fn random<R: Rng + ?Sized>(mut rng: &mut R, neurons_per_layer: &[usize]) -> Self {
+    // A network of ReLU layers with random weights and biases
+    pub fn random<R: Rng + ?Sized>(rng: &mut R, neurons_per_layer: &[usize]) -> Self {
+        let activations = vec![Activation::Relu; neurons_per_layer.len().saturating_sub(1)];
+        Self::random_with_activations(rng, neurons_per_layer, &activations)
+    }
+
+    // `activations` has an entry for every layer but the input layer
+    pub fn random_with_activations<R: Rng + ?Sized>(
+        mut rng: &mut R,
+        neurons_per_layer: &[usize],
+        activations: &[Activation],
+    ) -> Self {
         assert!(neurons_per_layer.len() > 1);
+        assert_eq!(activations.len(), neurons_per_layer.len() - 1);
 
         let range = Uniform::from(-1.0..1.0);
 
         // The first layer is intentionally not created, and instead the output values of what would be those neurons is represented with `inputs` in the propagate call
         let layers: Vec<Layer> = neurons_per_layer
             .windows(2)
-            .map(|n| Layer {
-                neurons: (0..n[1])
+            .zip(activations)
+            .map(|(n, &activation)| {
+                let neurons = (0..n[1])
                     .map(|_| Neuron {
                         bias: range.sample(&mut rng),
                         input_weights: (0..n[0]).map(|_| range.sample(&mut rng)).collect(),
                     })
-                    .collect(),
+                    .collect();
+                Layer::with_activation(neurons, activation)
             })
             .collect();
 
         Self { layers }
     }
 
+    // Propagate a single set of inputs, see propagate_batch for propagating repeatedly without allocating
     pub fn propagate(&self, inputs: &[f32]) -> Vec<f32> {
-        assert_eq!(inputs.len(), self.layers[0].neurons[0].input_weights.len());
+        assert_eq!(inputs.len(), self.input_size());
 
         let inputs = inputs.to_vec();
 
@@ -174,31 +366,167 @@ impl Network {
             .fold(inputs, |inputs, layer| layer.propagate(&inputs))
     }
 
+    // Propagate a batch of inputs at once, `inputs` being one row of `input_size` values per member of the batch
+    // Returns a row of `output_size` values per member, borrowed from the workspace
+    // Outputs are identical to calling propagate on each row
+    pub fn propagate_batch<'w>(&self, inputs: &[f32], workspace: &'w mut Workspace) -> &'w [f32] {
+        let (num_inputs, num_outputs) = (self.input_size(), self.output_size());
+        assert!(!inputs.is_empty() && inputs.len().is_multiple_of(num_inputs));
+        let batch = inputs.len() / num_inputs;
+
+        // Layers work on feature-major values, so transpose the rows on the way in and out
+        let Workspace { current, next } = workspace;
+        current.resize(inputs.len(), 0.0);
+        for (b, row) in inputs.chunks_exact(num_inputs).enumerate() {
+            for (i, &input) in row.iter().enumerate() {
+                current[i * batch + b] = input;
+            }
+        }
+
+        // Input the output of each layer into the next layer
+        for layer in &self.layers {
+            next.resize(batch * layer.output_size(), 0.0);
+            layer.propagate_batch(current, next, batch);
+            std::mem::swap(current, next);
+        }
+
+        next.resize(current.len(), 0.0);
+        for (b, row) in next.chunks_exact_mut(num_outputs).enumerate() {
+            for (o, output) in row.iter_mut().enumerate() {
+                *output = current[o * batch + b];
+            }
+        }
+
+        next
+    }
+
     pub fn input_size(&self) -> usize {
-        self.layers[0].neurons[0].input_weights.len()
+        self.layers[0].input_size()
     }
 
     pub fn output_size(&self) -> usize {
-        self.layers.last().unwrap().neurons.len()
+        self.layers.last().unwrap().output_size()
+    }
+
+    pub fn output_activation(&self) -> Activation {
+        self.layers.last().unwrap().activation
     }
 
-    pub fn crossover_uniform<R: Rng + ?Sized>(&self, mut rng: &mut R, b: &Self) -> Self {
+    // Build a child layer by layer from each pair of the parents' layers
+    fn crossover_layers(&self, b: &Self, mut layer: impl FnMut(&Layer, &Layer) -> Layer) -> Self {
         assert_eq!(self.layers.len(), b.layers.len());
         Self {
             layers: self
                 .layers
                 .iter()
-                .zip(b.layers.iter())
-                .map(|(a, b)| a.crossover_uniform(&mut rng, b))
+                .zip(&b.layers)
+                .map(|(a, b)| layer(a, b))
                 .collect(),
         }
     }
 
+    // Take each bias and weight from either parent
+    pub fn crossover_uniform<R: Rng + ?Sized>(&self, rng: &mut R, b: &Self) -> Self {
+        self.crossover_layers(b, |a, b| {
+            a.crossover(rng, b, &mut |rng, a, b| pick(rng, a, b))
+        })
+    }
+
+    // Cut the genome (every layer's biases and weights, neuron by neuron) at `points` random places
+    // and take alternating sections from each parent
+    pub fn crossover_k_point<R: Rng + ?Sized>(&self, rng: &mut R, b: &Self, points: usize) -> Self {
+        let num_genes: usize = self.layers.iter().map(Layer::num_genes).sum();
+        let points = points.min(num_genes - 1);
+        let mut cuts: Vec<usize> = rand::seq::index::sample(rng, num_genes - 1, points)
+            .into_iter()
+            .map(|i| i + 1)
+            .collect();
+        cuts.sort_unstable();
+
+        let mut cuts = cuts.into_iter().peekable();
+        let mut index = 0;
+        let mut from_b = false;
+        self.crossover_layers(b, |a, b| {
+            a.crossover(rng, b, &mut |_, a, b| {
+                if cuts.next_if_eq(&index).is_some() {
+                    from_b = !from_b;
+                }
+                index += 1;
+                if from_b {
+                    b
+                } else {
+                    a
+                }
+            })
+        })
+    }
+
+    // BLX-alpha, each value drawn uniformly from the range spanned by the parents' values
+    // extended by `alpha` times its width on both sides
+    pub fn crossover_blend<R: Rng + ?Sized>(&self, rng: &mut R, b: &Self, alpha: f32) -> Self {
+        self.crossover_layers(b, |a, b| {
+            a.crossover(rng, b, &mut |rng, a, b| {
+                let extent = alpha * (a - b).abs();
+                let min = a.min(b) - extent;
+                let max = a.max(b) + extent;
+                min + rng.gen::<f32>() * (max - min)
+            })
+        })
+    }
+
+    // Simulated binary crossover, each value spread around the parents' mean with a spread factor
+    // whose distribution narrows as `eta` grows, taking either of the two children it would create
+    pub fn crossover_sbx<R: Rng + ?Sized>(&self, rng: &mut R, b: &Self, eta: f32) -> Self {
+        self.crossover_layers(b, |a, b| {
+            a.crossover(rng, b, &mut |rng, a, b| {
+                let u = rng.gen::<f32>();
+                let beta = if u <= 0.5 {
+                    (2.0 * u).powf(1.0 / (eta + 1.0))
+                } else {
+                    (1.0 / (2.0 * (1.0 - u))).powf(1.0 / (eta + 1.0))
+                };
+                let spread = 0.5 * beta * (a - b);
+                pick(rng, 0.5 * (a + b) + spread, 0.5 * (a + b) - spread)
+            })
+        })
+    }
+
+    // Take whole neurons, bias and input weights together, from either parent
+    pub fn crossover_neuron<R: Rng + ?Sized>(&self, rng: &mut R, b: &Self) -> Self {
+        self.crossover_layers(b, |a, b| a.crossover_neurons(rng, b))
+    }
+
+    // Replace each bias and weight, with a chance of `probability`, by a new random value
     pub fn mutate_uniform<R: Rng + ?Sized>(&mut self, rng: &mut R, probability: f32) {
         let range = Uniform::from(-1.0..1.0);
 
         for l in &mut self.layers {
-            l.mutate_uniform(rng, range, probability);
+            l.mutate(rng, probability, &mut |rng, _| range.sample(rng));
+        }
+    }
+
+    // Nudge each bias and weight, with a chance of `probability`, by noise drawn from N(0, sigma)
+    // keeping the result within `bounds` if given
+    pub fn mutate_gaussian<R: Rng + ?Sized>(
+        &mut self,
+        rng: &mut R,
+        probability: f32,
+        sigma: f32,
+        bounds: Option<(f32, f32)>,
+    ) {
+        // A sigma that can't be sampled from leaves the network as it is
+        let Ok(noise) = Normal::new(0.0, sigma) else {
+            return;
+        };
+
+        for l in &mut self.layers {
+            l.mutate(rng, probability, &mut |rng, value| {
+                let value = value + noise.sample(rng);
+                match bounds {
+                    Some((min, max)) => value.clamp(min, max),
+                    None => value,
+                }
+            });
         }
     }
 }
@@ -211,13 +539,15 @@ mod tests {
     #[test]
     fn neuron_propagate() {
         let neuron = Neuron::new(0.1, vec![0.3, 0.4, 0.6]);
-        assert!((neuron.propagate(&[-0.1, 0.7, 0.3]) - 0.53).abs() < EPSILON);
+        assert!(
+            (neuron.propagate(&[-0.1, 0.7, 0.3], Activation::Relu) - 0.53).abs() < f32::EPSILON
+        );
     }
 
     #[test]
     fn neuron_propagate_zero() {
         let neuron = Neuron::new(-0.06, vec![0.6, 0.4, 0.5]);
-        assert!(neuron.propagate(&[-0.5, 0.3, 0.45]).abs() < EPSILON);
+        assert!(neuron.propagate(&[-0.5, 0.3, 0.45], Activation::Relu).abs() < f32::EPSILON);
     }
 
     #[test]
@@ -230,9 +560,9 @@ mod tests {
         let layer = Layer::new(neurons);
         let outputs = layer.propagate(&[0.7, 0.1]);
 
-        assert!((outputs[0] - 0.94).abs() < EPSILON);
-        assert!((outputs[1] - 0.99).abs() < EPSILON);
-        assert!((outputs[2] - 0.12).abs() < EPSILON);
+        assert!((outputs[0] - 0.94).abs() < f32::EPSILON);
+        assert!((outputs[1] - 0.99).abs() < f32::EPSILON);
+        assert!((outputs[2] - 0.12).abs() < f32::EPSILON);
     }
 
     #[test]
@@ -246,7 +576,40 @@ mod tests {
             Layer::new(vec![Neuron::new(0.5, vec![0.3, 0.4, 0.5])]),
         ]);
 
-        assert!((network.propagate(&[0.7, 0.1])[0] - 1.238).abs() < EPSILON);
+        assert!((network.propagate(&[0.7, 0.1])[0] - 1.238).abs() < f32::EPSILON);
+    }
+
+    #[test]
+    fn network_propagate_activations() {
+        let network = Network::from_layers(vec![
+            Layer::with_activation(
+                vec![
+                    Neuron::new(0.6, vec![0.4, 0.6]),
+                    Neuron::new(-0.8, vec![0.2, 0.5]),
+                ],
+                Activation::Identity,
+            ),
+            Layer::with_activation(vec![Neuron::new(0.5, vec![0.3, 0.4])], Activation::Tanh),
+        ]);
+
+        // hidden outputs are 0.94 and -0.61, which ReLU would have clamped to 0
+        let expected = (0.3f32 * 0.94 + 0.4 * -0.61 + 0.5).tanh();
+        assert!((network.propagate(&[0.7, 0.1])[0] - expected).abs() < 1e-6);
+        assert_eq!(network.output_activation(), Activation::Tanh);
+    }
+
+    #[test]
+    fn network_propagate_batch() {
+        let mut rng = Pcg64Mcg::new(0xcafef00dd15ea5e5);
+        let network = Network::random(&mut rng, &[3, 5, 2]);
+        let inputs = [0.1, 0.5, -0.3, 0.9, 0.2, 0.4, -0.7, 0.0, 0.6];
+
+        let mut workspace = Workspace::default();
+        let outputs = network.propagate_batch(&inputs, &mut workspace).to_vec();
+        assert_eq!(outputs.len(), 3 * 2);
+        for (inputs, outputs) in inputs.chunks(3).zip(outputs.chunks(2)) {
+            assert_eq!(network.propagate(inputs), outputs);
+        }
     }
 
     #[test]
@@ -266,6 +629,74 @@ mod tests {
         );
     }
 
+    #[test]
+    fn network_crossover_operators() {
+        let mut rng = Pcg64Mcg::new(0xcafef00dd15ea5e5);
+        let a = Network::random(&mut rng, &[9, 15, 2]);
+        let b = Network::random(&mut rng, &[9, 15, 2]);
+        let genes = |n: &Network| -> Vec<(f32, Vec<f32>)> {
+            n.layers
+                .iter()
+                .flat_map(|l| l.rows().map(|(bias, w)| (bias, w.to_vec())))
+                .collect()
+        };
+        let values = |n: &Network| -> Vec<f32> {
+            genes(n)
+                .into_iter()
+                .flat_map(|(bias, w)| std::iter::once(bias).chain(w))
+                .collect()
+        };
+        let (a_values, b_values) = (values(&a), values(&b));
+
+        // A single cut takes a prefix from one parent and the rest from the other
+        let c = values(&a.crossover_k_point(&mut rng, &b, 1));
+        let cut = c.iter().zip(&a_values).take_while(|(c, a)| c == a).count();
+        assert!(cut > 0 && cut < c.len());
+        assert_eq!(c[cut..], b_values[cut..]);
+
+        // Whole neurons come from one parent or the other
+        let c = a.crossover_neuron(&mut rng, &b);
+        for (c, (a, b)) in genes(&c).iter().zip(genes(&a).iter().zip(&genes(&b))) {
+            assert!(c == a || c == b);
+        }
+
+        // Blending without extending stays between the parents
+        let c = values(&a.crossover_blend(&mut rng, &b, 0.0));
+        for ((c, a), b) in c.iter().zip(&a_values).zip(&b_values) {
+            assert!(a.min(*b) <= *c && *c <= a.max(*b));
+        }
+        // Simulated binary crossover creates new values rather than copying the parents'
+        let c = values(&a.crossover_sbx(&mut rng, &b, 15.0));
+        assert!(c.iter().all(|c| c.is_finite()));
+        assert_ne!(c, a_values);
+    }
+
+    #[test]
+    fn network_mutate_gaussian() {
+        let mut rng = Pcg64Mcg::new(0xcafef00dd15ea5e5);
+        let original = Network::random(&mut rng, &[9, 15, 2]);
+        let values = |n: &Network| -> Vec<f32> {
+            n.layers
+                .iter()
+                .flat_map(|l| l.biases.iter().chain(&l.weights).copied())
+                .collect()
+        };
+
+        let mut unchanged = original.clone();
+        unchanged.mutate_gaussian(&mut rng, 0.0, 0.1, None);
+        unchanged.mutate_gaussian(&mut rng, 1.0, f32::INFINITY, None);
+        assert_eq!(values(&unchanged), values(&original));
+
+        // Every value moves, but only a little, and never past the bounds
+        let mut mutated = original.clone();
+        mutated.mutate_gaussian(&mut rng, 1.0, 0.1, Some((-1.0, 1.0)));
+        for (a, b) in values(&original).into_iter().zip(values(&mutated)) {
+            assert_ne!(a, b);
+            assert!((a - b).abs() < 0.6);
+            assert!((-1.0..=1.0).contains(&b));
+        }
+    }
+
     #[test]
     fn neuron_mutate() {
         let mut rng = Pcg64Mcg::new(0xcafef00dd15ea5e5);
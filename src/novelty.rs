@@ -0,0 +1,157 @@
+use crate::components::{Force, Position};
+use crate::neural::io::{read_f32, read_u32};
+use std::collections::VecDeque;
+use std::f32::consts::PI;
+use std::io::{self, Read, Write};
+
+// Behaviours of earlier generations, so that novelty is measured against the past as well as the population
+#[derive(Clone, Debug, Default)]
+pub struct NoveltyArchive {
+    capacity: usize,
+    // oldest first
+    descriptors: VecDeque<Vec<f32>>,
+}
+
+impl NoveltyArchive {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            descriptors: VecDeque::new(),
+        }
+    }
+
+    pub fn descriptors(&self) -> &VecDeque<Vec<f32>> {
+        &self.descriptors
+    }
+
+    // Dropping the oldest descriptor once the archive is full
+    pub fn add(&mut self, descriptor: Vec<f32>) {
+        if self.capacity == 0 {
+            return;
+        }
+        if self.descriptors.len() == self.capacity {
+            self.descriptors.pop_front();
+        }
+        self.descriptors.push_back(descriptor);
+    }
+
+    // u32 capacity and number of descriptors, followed by each descriptor's u32 length and f32 values
+    pub fn write<W: Write + ?Sized>(&self, w: &mut W) -> io::Result<()> {
+        w.write_all(&(self.capacity as u32).to_le_bytes())?;
+        w.write_all(&(self.descriptors.len() as u32).to_le_bytes())?;
+        for descriptor in &self.descriptors {
+            write_descriptor(w, descriptor)?;
+        }
+        Ok(())
+    }
+
+    pub fn read<R: Read + ?Sized>(r: &mut R) -> io::Result<Self> {
+        let capacity = read_u32(r)? as usize;
+        let count = read_u32(r)?;
+        let descriptors = (0..count)
+            .map(|_| read_descriptor(r))
+            .collect::<io::Result<VecDeque<Vec<f32>>>>()?;
+        Ok(Self {
+            capacity,
+            descriptors,
+        })
+    }
+}
+
+pub fn write_descriptor<W: Write + ?Sized>(w: &mut W, descriptor: &[f32]) -> io::Result<()> {
+    w.write_all(&(descriptor.len() as u32).to_le_bytes())?;
+    for value in descriptor {
+        w.write_all(&value.to_le_bytes())?;
+    }
+    Ok(())
+}
+
+pub fn read_descriptor<R: Read + ?Sized>(r: &mut R) -> io::Result<Vec<f32>> {
+    let len = read_u32(r)?;
+    (0..len).map(|_| read_f32(r)).collect()
+}
+
+pub fn distance(a: &[f32], b: &[f32]) -> f32 {
+    a.iter()
+        .zip(b)
+        .map(|(a, b)| (a - b) * (a - b))
+        .sum::<f32>()
+        .sqrt()
+}
+
+// Each descriptor's mean distance to the `k` nearest of the other descriptors and the archive's
+pub fn novelty(descriptors: &[Vec<f32>], archive: &NoveltyArchive, k: usize) -> Vec<f32> {
+    descriptors
+        .iter()
+        .enumerate()
+        .map(|(i, descriptor)| {
+            let mut distances: Vec<f32> = descriptors
+                .iter()
+                .enumerate()
+                .filter(|&(j, _)| j != i)
+                .map(|(_, other)| other)
+                .chain(archive.descriptors())
+                .map(|other| distance(descriptor, other))
+                .collect();
+            if distances.is_empty() {
+                return 0.0;
+            }
+            let k = k.min(distances.len());
+            distances.select_nth_unstable_by(k - 1, f32::total_cmp);
+            distances[..k].iter().sum::<f32>() / k as f32
+        })
+        .collect()
+}
+
+// A position as points on a circle around each axis of the arena, so that distances between positions
+// wrap around like the arena does
+pub fn position_descriptor(position: &Position, max: &Position) -> [f32; 4] {
+    let (x, y) = (2.0 * PI * position.x / max.x, 2.0 * PI * position.y / max.y);
+    [x.cos(), x.sin(), y.cos(), y.sin()]
+}
+
+// Index of the bin a force falls in, out of `bins` x `bins` over [-10, 10] for rotation and translation
+pub fn force_bin(force: &Force, bins: usize) -> usize {
+    let bin = |value: f32| (((value + 10.0) / 20.0 * bins as f32) as usize).min(bins - 1);
+    bin(force.rotation) * bins + bin(force.translation)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn nearest_neighbours_and_archive() {
+        let descriptors = vec![vec![0.0, 0.0], vec![1.0, 0.0], vec![10.0, 0.0]];
+        let mut archive = NoveltyArchive::new(2);
+        assert_eq!(novelty(&descriptors, &archive, 1), vec![1.0, 1.0, 9.0]);
+        assert_eq!(novelty(&descriptors, &archive, 5), vec![5.5, 5.0, 9.5]);
+
+        // An archived behaviour next to the outlier makes it less novel, the oldest are dropped when full
+        archive.add(vec![100.0, 0.0]);
+        archive.add(vec![9.0, 0.0]);
+        archive.add(vec![10.0, 1.0]);
+        assert_eq!(archive.descriptors().len(), 2);
+        assert_eq!(novelty(&descriptors, &archive, 1), vec![1.0, 1.0, 1.0]);
+
+        let mut buf = vec![];
+        archive.write(&mut buf).unwrap();
+        let read = NoveltyArchive::read(&mut buf.as_slice()).unwrap();
+        assert_eq!(read.descriptors(), archive.descriptors());
+
+        // Positions either side of an edge of the arena are close together
+        let max = Position { x: 100.0, y: 100.0 };
+        let a = position_descriptor(&Position { x: 1.0, y: 50.0 }, &max);
+        let b = position_descriptor(&Position { x: 99.0, y: 50.0 }, &max);
+        let c = position_descriptor(&Position { x: 50.0, y: 50.0 }, &max);
+        assert!(distance(&a, &b) < distance(&a, &c) / 10.0);
+
+        let force = |rotation, translation| Force {
+            rotation,
+            translation,
+        };
+        assert_eq!(force_bin(&force(-10.0, -10.0), 4), 0);
+        assert_eq!(force_bin(&force(10.0, 10.0), 4), 15);
+        assert_eq!(force_bin(&force(0.0, -6.0), 4), 8);
+    }
+}
@@ -0,0 +1,378 @@
+use crate::components::Position;
+use crate::config::ObstacleCollision;
+use serde::{Deserialize, Serialize};
+
+// Room left between an agent stopped by an obstacle and its surface, so that it's never counted as inside it
+const STANDOFF: f32 = 0.01;
+
+// Random attempts at placing something outside every obstacle before searching the arena for room
+const PLACEMENT_ATTEMPTS: usize = 100;
+
+// Points along each side of the grid that the arena is searched on for room
+const GRID_POINTS: usize = 100;
+
+// The outline of a static obstacle, in arena coordinates
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Serialize)]
+#[serde(tag = "shape", rename_all = "snake_case", deny_unknown_fields)]
+pub enum Shape {
+    Circle { x: f32, y: f32, radius: f32 },
+    // axis-aligned, between its corners with the smallest and largest x and y
+    Box { min: (f32, f32), max: (f32, f32) },
+    // a line segment with no thickness, which can be seen and hit from either side
+    Wall { from: (f32, f32), to: (f32, f32) },
+}
+
+// Where a point moving in a straight line first meets a shape
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Hit {
+    // fraction of the way along the line
+    pub t: f32,
+    // unit normal of the surface, facing back the way the point came
+    pub normal: (f32, f32),
+}
+
+fn dot(a: (f32, f32), b: (f32, f32)) -> f32 {
+    a.0 * b.0 + a.1 * b.1
+}
+
+fn cross(a: (f32, f32), b: (f32, f32)) -> f32 {
+    a.0 * b.1 - a.1 * b.0
+}
+
+impl Shape {
+    pub fn shifted(&self, dx: f32, dy: f32) -> Self {
+        match *self {
+            Shape::Circle { x, y, radius } => Shape::Circle {
+                x: x + dx,
+                y: y + dy,
+                radius,
+            },
+            Shape::Box { min, max } => Shape::Box {
+                min: (min.0 + dx, min.1 + dy),
+                max: (max.0 + dx, max.1 + dy),
+            },
+            Shape::Wall { from, to } => Shape::Wall {
+                from: (from.0 + dx, from.1 + dy),
+                to: (to.0 + dx, to.1 + dy),
+            },
+        }
+    }
+
+    // Walls have no inside
+    pub fn contains(&self, p: &Position) -> bool {
+        match *self {
+            Shape::Circle { x, y, radius } => {
+                (p.x - x).powi(2) + (p.y - y).powi(2) < radius * radius
+            }
+            Shape::Box { min, max } => p.x > min.0 && p.x < max.0 && p.y > min.1 && p.y < max.1,
+            Shape::Wall { .. } => false,
+        }
+    }
+
+    // Where the line from `from` to `to` first enters the shape, if it does
+    // A line starting inside a shape never hits it, so anything that ends up inside can get back out
+    pub fn hit(&self, from: &Position, to: &Position) -> Option<Hit> {
+        if self.contains(from) {
+            return None;
+        }
+        let d = (to.x - from.x, to.y - from.y);
+        if d == (0.0, 0.0) {
+            return None;
+        }
+
+        match *self {
+            Shape::Circle { x, y, radius } => {
+                let f = (from.x - x, from.y - y);
+                let a = dot(d, d);
+                let b = 2.0 * dot(f, d);
+                let c = dot(f, f) - radius * radius;
+                let discriminant = b * b - 4.0 * a * c;
+                if discriminant < 0.0 {
+                    return None;
+                }
+                let t = (-b - discriminant.sqrt()) / (2.0 * a);
+                if !(0.0..=1.0).contains(&t) {
+                    return None;
+                }
+                let normal = ((f.0 + d.0 * t) / radius, (f.1 + d.1 * t) / radius);
+                Some(Hit { t, normal })
+            }
+            Shape::Box { min, max } => {
+                // Slabs between each pair of opposite sides, the line enters the box once it's inside both
+                let mut enter = f32::NEG_INFINITY;
+                let mut exit = f32::INFINITY;
+                let mut normal = (0.0, 0.0);
+                for (start, delta, low, high, axis) in [
+                    (from.x, d.0, min.0, max.0, (1.0, 0.0)),
+                    (from.y, d.1, min.1, max.1, (0.0, 1.0)),
+                ] {
+                    if delta == 0.0 {
+                        if start <= low || start >= high {
+                            return None;
+                        }
+                        continue;
+                    }
+                    let (t1, t2) = ((low - start) / delta, (high - start) / delta);
+                    let (near, far) = (t1.min(t2), t1.max(t2));
+                    if near > enter {
+                        enter = near;
+                        let sign = if delta > 0.0 { -1.0 } else { 1.0 };
+                        normal = (axis.0 * sign, axis.1 * sign);
+                    }
+                    exit = exit.min(far);
+                }
+                (enter <= exit && (0.0..=1.0).contains(&enter)).then_some(Hit { t: enter, normal })
+            }
+            Shape::Wall { from: a, to: b } => {
+                let e = (b.0 - a.0, b.1 - a.1);
+                let denominator = cross(d, e);
+                if denominator == 0.0 {
+                    return None;
+                }
+                let offset = (a.0 - from.x, a.1 - from.y);
+                let t = cross(offset, e) / denominator;
+                let u = cross(offset, d) / denominator;
+                if !(0.0..=1.0).contains(&t) || !(0.0..=1.0).contains(&u) {
+                    return None;
+                }
+                let length = dot(e, e).sqrt();
+                let mut normal = (-e.1 / length, e.0 / length);
+                if dot(normal, d) > 0.0 {
+                    normal = (-normal.0, -normal.1);
+                }
+                Some(Hit { t, normal })
+            }
+        }
+    }
+
+    // Every number the shape is made of
+    fn values(&self) -> [f32; 4] {
+        match *self {
+            Shape::Circle { x, y, radius } => [x, y, radius, 0.0],
+            Shape::Box { min, max } => [min.0, min.1, max.0, max.1],
+            Shape::Wall { from, to } => [from.0, from.1, to.0, to.1],
+        }
+    }
+
+    // Why the shape can't be used, if it can't
+    pub fn check(&self) -> Result<(), String> {
+        if !self.values().iter().all(|v| v.is_finite()) {
+            return Err(format!(
+                "obstacle coordinates must all be finite numbers, got {self:?}"
+            ));
+        }
+        match *self {
+            Shape::Circle { radius, .. } if radius <= 0.0 => Err(format!(
+                "obstacle circles must have a radius greater than 0, got {radius}"
+            )),
+            Shape::Box { min, max } if !(min.0 < max.0 && min.1 < max.1) => Err(format!(
+                "obstacle boxes must have a min below their max, got {min:?} and {max:?}"
+            )),
+            Shape::Wall { from, to } if from == to => Err(format!(
+                "obstacle walls must have two different ends, got {from:?}"
+            )),
+            _ => Ok(()),
+        }
+    }
+}
+
+// Each shape along with its copies across the edges of the wrapping arena
+fn wrapped<'a>(shapes: &'a [Shape], max: &'a Position) -> impl Iterator<Item = Shape> + 'a {
+    shapes.iter().flat_map(move |shape| {
+        [-max.x, 0.0, max.x]
+            .into_iter()
+            .flat_map(move |dx| [-max.y, 0.0, max.y].map(|dy| shape.shifted(dx, dy)))
+    })
+}
+
+// The first of `shapes` in the way of a line from `from` to `to`
+pub fn first_hit(shapes: &[Shape], from: &Position, to: &Position, max: &Position) -> Option<Hit> {
+    wrapped(shapes, max)
+        .filter_map(|shape| shape.hit(from, to))
+        .min_by(|a, b| a.t.total_cmp(&b.t))
+}
+
+pub fn inside(shapes: &[Shape], p: &Position, max: &Position) -> bool {
+    wrapped(shapes, max).any(|shape| shape.contains(p))
+}
+
+// The centres of a grid of cells covering the arena
+fn grid(max: &Position) -> impl Iterator<Item = Position> + '_ {
+    let cell = |i: usize, size: f32| (i as f32 + 0.5) * size / GRID_POINTS as f32;
+    (0..GRID_POINTS).flat_map(move |i| {
+        (0..GRID_POINTS).map(move |j| Position {
+            x: cell(i, max.x),
+            y: cell(j, max.y),
+        })
+    })
+}
+
+// Whether anything can be placed outside the obstacles, going by a grid over the arena
+pub fn has_room(shapes: &[Shape], max: &Position) -> bool {
+    grid(max).any(|p| !inside(shapes, &p, max))
+}
+
+// Sample positions until one is outside every obstacle
+// Without obstacles the first sample is always taken, so runs without them place things exactly as before
+// If none of the samples are, take the point of the grid closest to the last one that is
+pub fn place(shapes: &[Shape], max: &Position, mut sample: impl FnMut() -> Position) -> Position {
+    let mut position = sample();
+    for _ in 1..PLACEMENT_ATTEMPTS {
+        if !inside(shapes, &position, max) {
+            return position;
+        }
+        position = sample();
+    }
+    if !inside(shapes, &position, max) {
+        return position;
+    }
+
+    let distance = |p: &Position| (p.x - position.x).powi(2) + (p.y - position.y).powi(2);
+    grid(max)
+        .filter(|p| !inside(shapes, p, max))
+        .min_by(|a, b| distance(a).total_cmp(&distance(b)))
+        .unwrap_or(position)
+}
+
+// Where an agent moving from `from` towards `to` ends up, either stopped just short of the first obstacle in
+// its way or sliding along its surface for the rest of the move, until it meets another
+pub fn deflect(
+    shapes: &[Shape],
+    from: &Position,
+    to: &Position,
+    max: &Position,
+    collision: ObstacleCollision,
+) -> Position {
+    let stop_at = |from: &Position, to: &Position, hit: Hit| Position {
+        x: from.x + (to.x - from.x) * hit.t + hit.normal.0 * STANDOFF,
+        y: from.y + (to.y - from.y) * hit.t + hit.normal.1 * STANDOFF,
+    };
+    let Some(hit) = first_hit(shapes, from, to, max) else {
+        return *to;
+    };
+    let contact = stop_at(from, to, hit);
+    if collision == ObstacleCollision::Stop {
+        return contact;
+    }
+
+    // The rest of the move, less the part of it that's into the surface
+    let rest = (
+        (to.x - from.x) * (1.0 - hit.t),
+        (to.y - from.y) * (1.0 - hit.t),
+    );
+    let into = dot(rest, hit.normal);
+    let slid = Position {
+        x: contact.x + rest.0 - hit.normal.0 * into,
+        y: contact.y + rest.1 - hit.normal.1 * into,
+    };
+    match first_hit(shapes, &contact, &slid, max) {
+        Some(next) => stop_at(&contact, &slid, next),
+        None => slid,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn p(x: f32, y: f32) -> Position {
+        Position { x, y }
+    }
+
+    #[test]
+    fn hits_stops_and_slides() {
+        let max = p(100.0, 100.0);
+        let circle = Shape::Circle {
+            x: 50.0,
+            y: 50.0,
+            radius: 10.0,
+        };
+        let hit = circle.hit(&p(30.0, 50.0), &p(50.0, 50.0)).unwrap();
+        assert_eq!(
+            hit,
+            Hit {
+                t: 0.5,
+                normal: (-1.0, 0.0)
+            }
+        );
+        assert!(circle.hit(&p(30.0, 70.0), &p(50.0, 70.0)).is_none());
+
+        let square = Shape::Box {
+            min: (40.0, 40.0),
+            max: (60.0, 60.0),
+        };
+        let hit = square.hit(&p(50.0, 80.0), &p(50.0, 40.0)).unwrap();
+        assert_eq!(
+            hit,
+            Hit {
+                t: 0.5,
+                normal: (0.0, 1.0)
+            }
+        );
+        assert!(square.contains(&p(45.0, 45.0)));
+
+        // Walls are hit from either side, with the normal facing back
+        let wall = Shape::Wall {
+            from: (10.0, 0.0),
+            to: (10.0, 20.0),
+        };
+        assert_eq!(
+            wall.hit(&p(0.0, 5.0), &p(20.0, 5.0)).unwrap().normal,
+            (-1.0, 0.0)
+        );
+        assert_eq!(
+            wall.hit(&p(20.0, 5.0), &p(0.0, 5.0)).unwrap().normal,
+            (1.0, 0.0)
+        );
+        assert!(wall.hit(&p(0.0, 25.0), &p(20.0, 25.0)).is_none());
+
+        // Moving diagonally into the wall stops at it, or slides along it
+        let shapes = [wall];
+        let (from, to) = (p(5.0, 5.0), p(15.0, 15.0));
+        let stopped = deflect(&shapes, &from, &to, &max, ObstacleCollision::Stop);
+        assert!((stopped.x - 10.0).abs() < 0.1 && stopped.x < 10.0);
+        assert!((stopped.y - 10.0).abs() < 0.1);
+        let slid = deflect(&shapes, &from, &to, &max, ObstacleCollision::Slide);
+        assert!(slid.x < 10.0 && (slid.y - 15.0).abs() < 0.1);
+
+        // Obstacles are copied across the arena's edges like everything else
+        let edge = [Shape::Circle {
+            x: 0.0,
+            y: 50.0,
+            radius: 5.0,
+        }];
+        assert!(first_hit(&edge, &p(90.0, 50.0), &p(99.0, 50.0), &max).is_some());
+        assert!(inside(&edge, &p(98.0, 50.0), &max));
+
+        let mut samples = [p(50.0, 50.0), p(99.0, 50.0), p(20.0, 20.0)].into_iter();
+        let placed = place(&[circle, edge[0]], &max, || samples.next().unwrap());
+        assert_eq!((placed.x, placed.y), (20.0, 20.0));
+
+        // Once sampling gives up, whatever room is left is found
+        let walls = [
+            Shape::Box {
+                min: (0.0, 0.0),
+                max: (90.0, 100.0),
+            },
+            Shape::Box {
+                min: (89.0, -10.0),
+                max: (110.0, 10.0),
+            },
+        ];
+        assert!(has_room(&walls, &max));
+        let placed = place(&walls, &max, || p(50.0, 60.2));
+        assert!(!inside(&walls, &placed, &max));
+        assert_eq!((placed.x, placed.y), (90.5, 60.5));
+        let everywhere = [Shape::Box {
+            min: (-10.0, -10.0),
+            max: (110.0, 110.0),
+        }];
+        assert!(!has_room(&everywhere, &max));
+
+        let nowhere = Shape::Wall {
+            from: (0.0, f32::NAN),
+            to: (10.0, 10.0),
+        };
+        assert!(nowhere.check().is_err());
+    }
+}
@@ -0,0 +1,150 @@
+// Pareto ranking as in NSGA-II, over vectors of objectives that are all to be maximised
+
+// `a` dominates `b` when it's at least as good in every objective and better in one
+pub fn dominates(a: &[f32], b: &[f32]) -> bool {
+    a.iter().zip(b).all(|(a, b)| a >= b) && a.iter().zip(b).any(|(a, b)| a > b)
+}
+
+// Indices of each non-dominated front, best first
+// Fast non-dominated sort: every front is made of whatever only the fronts before it dominate
+pub fn fronts(objectives: &[Vec<f32>]) -> Vec<Vec<usize>> {
+    let n = objectives.len();
+    let mut dominated: Vec<Vec<usize>> = vec![vec![]; n];
+    let mut domination_count = vec![0; n];
+    for a in 0..n {
+        for b in 0..n {
+            if dominates(&objectives[a], &objectives[b]) {
+                dominated[a].push(b);
+            } else if dominates(&objectives[b], &objectives[a]) {
+                domination_count[a] += 1;
+            }
+        }
+    }
+
+    let mut fronts = vec![];
+    let mut front: Vec<usize> = (0..n).filter(|&i| domination_count[i] == 0).collect();
+    while !front.is_empty() {
+        let mut next = vec![];
+        for &a in &front {
+            for &b in &dominated[a] {
+                domination_count[b] -= 1;
+                if domination_count[b] == 0 {
+                    next.push(b);
+                }
+            }
+        }
+        next.sort_unstable();
+        fronts.push(front);
+        front = next;
+    }
+    fronts
+}
+
+// How far apart each member of a front is from its neighbours, summed over the objectives and
+// normalised by each objective's range, with the ends of every objective infinitely far apart
+pub fn crowding_distances(objectives: &[Vec<f32>], front: &[usize]) -> Vec<f32> {
+    let mut distances = vec![0.0; front.len()];
+    if front.len() < 3 {
+        distances.fill(f32::INFINITY);
+        return distances;
+    }
+
+    // Each objective's values across the front
+    let columns: Vec<Vec<f32>> = (0..objectives[front[0]].len())
+        .map(|m| front.iter().map(|&i| objectives[i][m]).collect())
+        .collect();
+    let mut order: Vec<usize> = (0..front.len()).collect();
+    for column in &columns {
+        let value = |i: usize| column[i];
+        order.sort_by(|&a, &b| value(a).total_cmp(&value(b)));
+
+        let (first, last) = (order[0], order[order.len() - 1]);
+        distances[first] = f32::INFINITY;
+        distances[last] = f32::INFINITY;
+        let range = value(last) - value(first);
+        if range <= 0.0 {
+            continue;
+        }
+        for w in order.windows(3) {
+            distances[w[1]] += (value(w[2]) - value(w[0])) / range;
+        }
+    }
+    distances
+}
+
+// Where each member of a population ranks
+#[derive(Clone, Debug, PartialEq)]
+pub struct ParetoRank {
+    // 0 for the non-dominated front
+    pub front: u32,
+    pub crowding: f32,
+}
+
+pub fn rank(objectives: &[Vec<f32>]) -> Vec<ParetoRank> {
+    let mut ranks = vec![
+        ParetoRank {
+            front: 0,
+            crowding: 0.0,
+        };
+        objectives.len()
+    ];
+    for (f, front) in fronts(objectives).iter().enumerate() {
+        for (&i, crowding) in front.iter().zip(crowding_distances(objectives, front)) {
+            ranks[i] = ParetoRank {
+                front: f as u32,
+                crowding,
+            };
+        }
+    }
+    ranks
+}
+
+// A score per member that orders them by the crowded comparison, earlier fronts first and then the
+// less crowded within a front, for strategies that pick by score
+pub fn crowded_scores(ranks: &[ParetoRank]) -> Vec<f32> {
+    let mut order: Vec<usize> = (0..ranks.len()).collect();
+    order.sort_by(|&a, &b| {
+        let (a, b) = (&ranks[a], &ranks[b]);
+        a.front
+            .cmp(&b.front)
+            .then(b.crowding.total_cmp(&a.crowding))
+    });
+
+    let mut scores = vec![0.0; ranks.len()];
+    let mut score = ranks.len() as f32;
+    for (i, &member) in order.iter().enumerate() {
+        if i > 0 && ranks[order[i - 1]] != ranks[member] {
+            score = (ranks.len() - i) as f32;
+        }
+        scores[member] = score;
+    }
+    scores
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fronts_and_crowding() {
+        // Maximising both, (5, 1), (3, 3) and (1, 5) trade off, (2, 2) is only beaten by (3, 3)
+        // and (0, 0) is beaten by everything
+        let objectives = vec![
+            vec![2.0, 2.0],
+            vec![5.0, 1.0],
+            vec![0.0, 0.0],
+            vec![3.0, 3.0],
+            vec![1.0, 5.0],
+        ];
+        assert_eq!(fronts(&objectives), vec![vec![1, 3, 4], vec![0], vec![2]]);
+
+        let ranks = rank(&objectives);
+        assert_eq!(ranks[3].front, 0);
+        assert!(ranks[3].crowding.is_finite());
+        assert!(ranks[1].crowding.is_infinite() && ranks[4].crowding.is_infinite());
+
+        // The ends of the first front tie, ahead of its middle, then the later fronts in order
+        let scores = crowded_scores(&ranks);
+        assert_eq!(scores, vec![2.0, 5.0, 1.0, 3.0, 5.0]);
+    }
+}
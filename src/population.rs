@@ -0,0 +1,129 @@
+use crate::neural::io::{
+    check_binary_header, check_text_header, invalid_data, read_line, read_u32, BINARY_MAGIC,
+    TEXT_HEADER,
+};
+use crate::neural::Network;
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, BufWriter, Write};
+use std::path::Path;
+
+// A population is a count of networks followed by each network in the same form (see neural::io)
+//
+// Text, version 1:
+//
+//   genetic-population 1
+//   networks 30
+//   genetic-network 1
+//   ...
+//
+// Binary, version 1, little-endian:
+//
+//   4 bytes   magic, "GPOP"
+//   u32       format version
+//   u32       number of networks
+//   ...       each network in its binary form
+//
+// A file holding a single network in either form also loads as a population of one
+
+const POPULATION_TEXT_HEADER: &str = "genetic-population";
+const POPULATION_BINARY_MAGIC: &[u8; 4] = b"GPOP";
+const VERSION: u32 = 1;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Format {
+    Text,
+    Binary,
+}
+
+pub fn write<W: Write + ?Sized>(w: &mut W, networks: &[Network], format: Format) -> io::Result<()> {
+    match format {
+        Format::Text => {
+            writeln!(w, "{POPULATION_TEXT_HEADER} {VERSION}")?;
+            writeln!(w, "networks {}", networks.len())?;
+            for network in networks {
+                network.write_text(w)?;
+            }
+        }
+        Format::Binary => {
+            w.write_all(POPULATION_BINARY_MAGIC)?;
+            w.write_all(&VERSION.to_le_bytes())?;
+            w.write_all(&(networks.len() as u32).to_le_bytes())?;
+            for network in networks {
+                network.write_binary(w)?;
+            }
+        }
+    }
+    Ok(())
+}
+
+pub fn read<R: BufRead + ?Sized>(r: &mut R) -> io::Result<Vec<Network>> {
+    let start = r.fill_buf()?;
+    if start.starts_with(BINARY_MAGIC) || start.starts_with(TEXT_HEADER.as_bytes()) {
+        return Ok(vec![Network::read(r)?]);
+    }
+
+    if start.starts_with(POPULATION_BINARY_MAGIC) {
+        check_binary_header(r, POPULATION_BINARY_MAGIC, VERSION)?;
+        let count = read_u32(r)?;
+        (0..count).map(|_| Network::read_binary(r)).collect()
+    } else {
+        check_text_header(&read_line(r)?, POPULATION_TEXT_HEADER, VERSION)?;
+        let count = read_line(r)?
+            .strip_prefix("networks ")
+            .and_then(|n| n.trim().parse::<usize>().ok())
+            .ok_or_else(|| invalid_data("expected number of networks"))?;
+        (0..count).map(|_| Network::read_text(r)).collect()
+    }
+}
+
+pub fn save<P: AsRef<Path>>(path: P, networks: &[Network], format: Format) -> io::Result<()> {
+    let mut w = BufWriter::new(File::create(path)?);
+    write(&mut w, networks, format)?;
+    w.flush()
+}
+
+pub fn load<P: AsRef<Path>>(path: P) -> io::Result<Vec<Network>> {
+    let mut r = BufReader::new(File::open(path)?);
+    let networks = read(&mut r)?;
+    if !r.fill_buf()?.is_empty() {
+        return Err(invalid_data("unexpected data after the last network"));
+    }
+    Ok(networks)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand_pcg::Pcg64Mcg;
+
+    #[test]
+    fn round_trip() {
+        let mut rng = Pcg64Mcg::new(0xcafef00dd15ea5e5);
+        let networks: Vec<Network> = (0..3)
+            .map(|_| Network::random(&mut rng, &[5, 4, 2]))
+            .collect();
+
+        for format in [Format::Text, Format::Binary] {
+            let mut buf = vec![];
+            write(&mut buf, &networks, format).unwrap();
+            let read = read(&mut buf.as_slice()).unwrap();
+            assert_eq!(read.len(), 3);
+            for (a, b) in networks.iter().zip(&read) {
+                assert_eq!(
+                    a.propagate(&[0.1, 0.2, 0.3, 0.4, 0.5]),
+                    b.propagate(&[0.1, 0.2, 0.3, 0.4, 0.5])
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn single_network() {
+        let mut rng = Pcg64Mcg::new(0xcafef00dd15ea5e5);
+        let mut buf = vec![];
+        Network::random(&mut rng, &[5, 2])
+            .write_text(&mut buf)
+            .unwrap();
+        assert_eq!(read(&mut buf.as_slice()).unwrap().len(), 1);
+    }
+}
@@ -0,0 +1,228 @@
+use crate::components::{Position, Velocity};
+use crate::config::Config;
+use crate::obstacles::Shape;
+use crate::resources::Ticks;
+use specs::{prelude::*, ReadExpect, ReadStorage, RunningTime, System};
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, BufWriter, Lines, Write};
+use std::path::Path;
+
+// A recording is a header line with the arena size and tickrate, a line for each obstacle (a circle's centre and
+// radius, a box's corners or a wall's ends), then every tick of the run
+// Each tick starts with a `tick` line and then has a line for every agent (position and heading) and target (position)
+//
+//   recording 1200 1200 120
+//   c 600 600 50
+//   b 100 100 200 150
+//   w 0 300 400 300
+//   tick 1
+//   a 10.5 300.25 1.5707964
+//   t 80 45.5
+
+// Writes the state of the world at the end of every tick
+pub struct Recorder {
+    // None once writing has failed, after which nothing more is recorded
+    w: Option<BufWriter<File>>,
+}
+
+impl Recorder {
+    pub fn create<P: AsRef<Path>>(path: P, config: &Config) -> io::Result<Self> {
+        let mut w = BufWriter::new(File::create(path)?);
+        writeln!(
+            w,
+            "recording {} {} {}",
+            config.arena.width, config.arena.height, config.simulation.tickrate
+        )?;
+        for shape in &config.obstacles.shapes {
+            match *shape {
+                Shape::Circle { x, y, radius } => writeln!(w, "c {x} {y} {radius}"),
+                Shape::Box { min, max } => writeln!(w, "b {} {} {} {}", min.0, min.1, max.0, max.1),
+                Shape::Wall { from, to } => {
+                    writeln!(w, "w {} {} {} {}", from.0, from.1, to.0, to.1)
+                }
+            }?;
+        }
+        Ok(Self { w: Some(w) })
+    }
+}
+
+fn write_tick(
+    w: &mut BufWriter<File>,
+    positions: &ReadStorage<Position>,
+    velocities: &ReadStorage<Velocity>,
+    tick: u64,
+) -> io::Result<()> {
+    writeln!(w, "tick {tick}")?;
+    for (p, v) in (positions, velocities.maybe()).join() {
+        if let Some(v) = v {
+            writeln!(w, "a {} {} {}", p.x, p.y, v.heading)?;
+        } else {
+            writeln!(w, "t {} {}", p.x, p.y)?;
+        }
+    }
+    Ok(())
+}
+
+impl<'a> System<'a> for Recorder {
+    type SystemData = (
+        ReadStorage<'a, Position>,
+        ReadStorage<'a, Velocity>,
+        ReadExpect<'a, Ticks>,
+    );
+
+    fn run(&mut self, (positions, velocities, ticks): Self::SystemData) {
+        let Some(w) = &mut self.w else {
+            return;
+        };
+        // A failed write shouldn't end the run, so recording stops instead
+        if let Err(e) = write_tick(w, &positions, &velocities, ticks.get()) {
+            eprintln!("Unable to write recording, no more will be recorded: {e}");
+            self.w = None;
+        }
+    }
+
+    fn running_time(&self) -> RunningTime {
+        RunningTime::Short
+    }
+}
+
+// The state of the world at the end of a recorded tick
+#[derive(Debug, Default)]
+pub struct Frame {
+    pub tick: u64,
+    // positions and headings
+    pub agents: Vec<(Position, f32)>,
+    pub targets: Vec<Position>,
+}
+
+// Reads a recording one frame at a time
+pub struct Replay {
+    pub width: u32,
+    pub height: u32,
+    pub tickrate: u32,
+    pub obstacles: Vec<Shape>,
+    lines: Lines<BufReader<File>>,
+    next_tick: Option<u64>,
+}
+
+fn invalid_data(msg: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, msg)
+}
+
+fn parse_values<const N: usize>(
+    line: &str,
+    fields: &mut std::str::SplitWhitespace,
+) -> io::Result<[f32; N]> {
+    let mut values = [0.0; N];
+    for v in &mut values {
+        *v = fields
+            .next()
+            .and_then(|f| f.parse().ok())
+            .ok_or_else(|| invalid_data(format!("invalid recording line `{line}`")))?;
+    }
+    Ok(values)
+}
+
+impl Replay {
+    pub fn open<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        let mut lines = BufReader::new(File::open(path)?).lines();
+        let header = lines.next().unwrap_or_else(|| Ok(String::new()))?;
+        let header: Vec<u32> = header
+            .strip_prefix("recording ")
+            .map(|h| {
+                h.split_whitespace()
+                    .filter_map(|v| v.parse().ok())
+                    .collect()
+            })
+            .unwrap_or_default();
+        if header.len() != 3 {
+            return Err(invalid_data("expected recording header".to_owned()));
+        }
+
+        let mut replay = Self {
+            width: header[0],
+            height: header[1],
+            tickrate: header[2],
+            obstacles: vec![],
+            lines,
+            next_tick: None,
+        };
+        replay.read_obstacles()?;
+        Ok(replay)
+    }
+
+    // Obstacle lines up to the first tick line, which recordings from before obstacles go straight to
+    fn read_obstacles(&mut self) -> io::Result<()> {
+        for line in &mut self.lines {
+            let line = line?;
+            let mut fields = line.split_whitespace();
+            let shape = match fields.next() {
+                Some("c") => {
+                    let [x, y, radius] = parse_values(&line, &mut fields)?;
+                    Shape::Circle { x, y, radius }
+                }
+                Some("b") => {
+                    let [x0, y0, x1, y1] = parse_values(&line, &mut fields)?;
+                    Shape::Box {
+                        min: (x0, y0),
+                        max: (x1, y1),
+                    }
+                }
+                Some("w") => {
+                    let [x0, y0, x1, y1] = parse_values(&line, &mut fields)?;
+                    Shape::Wall {
+                        from: (x0, y0),
+                        to: (x1, y1),
+                    }
+                }
+                Some("tick") => {
+                    self.next_tick = fields.next().and_then(|t| t.parse().ok());
+                    if self.next_tick.is_none() {
+                        return Err(invalid_data(format!("expected tick, found `{line}`")));
+                    }
+                    break;
+                }
+                _ => return Err(invalid_data(format!("expected tick, found `{line}`"))),
+            };
+            self.obstacles.push(shape);
+        }
+        Ok(())
+    }
+
+    // The next recorded frame, or None at the end of the recording
+    pub fn next_frame(&mut self) -> io::Result<Option<Frame>> {
+        let tick = match self.next_tick.take() {
+            Some(tick) => tick,
+            None => return Ok(None),
+        };
+        let mut frame = Frame {
+            tick,
+            ..Frame::default()
+        };
+
+        for line in &mut self.lines {
+            let line = line?;
+            let mut fields = line.split_whitespace();
+            match fields.next() {
+                Some("a") => {
+                    let [x, y, heading] = parse_values(&line, &mut fields)?;
+                    frame.agents.push((Position { x, y }, heading));
+                }
+                Some("t") => {
+                    let [x, y] = parse_values(&line, &mut fields)?;
+                    frame.targets.push(Position { x, y });
+                }
+                Some("tick") => {
+                    self.next_tick = fields.next().and_then(|t| t.parse().ok());
+                    if self.next_tick.is_none() {
+                        return Err(invalid_data(format!("invalid recording line `{line}`")));
+                    }
+                    break;
+                }
+                _ => return Err(invalid_data(format!("invalid recording line `{line}`"))),
+            }
+        }
+
+        Ok(Some(frame))
+    }
+}
@@ -0,0 +1,245 @@
+use genetic::components::{Position, Velocity};
+use genetic::config::Config;
+use genetic::obstacles::Shape;
+use genetic::recording::Replay;
+use genetic::resources::GenerationEvent;
+use genetic::simulation::{self, Simulation, StopAfter};
+use sdl2::event::Event;
+use sdl2::gfx::framerate::FPSManager;
+use sdl2::gfx::primitives::DrawRenderer;
+use sdl2::keyboard::Keycode;
+use sdl2::pixels::Color;
+use sdl2::render::Canvas;
+use sdl2::video::Window;
+use sdl2::{EventPump, Sdl};
+use specs::{prelude::*, WorldExt};
+use std::f32::consts::PI;
+use std::io;
+use std::time::Instant;
+
+const BLACK: Color = Color::RGBA(30, 30, 30, 255);
+const WHITE: Color = Color::RGBA(225, 225, 225, 255);
+const GREY: Color = Color::RGBA(120, 120, 120, 255);
+
+struct Screen {
+    _sdl_context: Sdl,
+    canvas: Canvas<Window>,
+    event_pump: EventPump,
+    // None when the tickrate is beyond what SDL can cap to, in which case frames aren't delayed at all
+    fps_manager: Option<FPSManager>,
+}
+
+impl Screen {
+    fn open(width: u32, height: u32, tickrate: u32) -> Self {
+        let sdl_context = sdl2::init().unwrap();
+        let video_subsystem = sdl_context.video().unwrap();
+
+        let window = video_subsystem
+            .window("genetic", width, height)
+            .position_centered()
+            .build()
+            .unwrap();
+
+        let mut canvas = window.into_canvas().build().unwrap();
+
+        canvas.set_draw_color(BLACK);
+        canvas.clear();
+        canvas.present();
+
+        let event_pump = sdl_context.event_pump().unwrap();
+        let mut fps_manager = FPSManager::new();
+        let fps_manager = match fps_manager.set_framerate(tickrate) {
+            Ok(()) => Some(fps_manager),
+            Err(e) => {
+                eprintln!("Unable to cap framerate to {tickrate}, rendering uncapped: {e}");
+                None
+            }
+        };
+
+        Self {
+            _sdl_context: sdl_context,
+            canvas,
+            event_pump,
+            fps_manager,
+        }
+    }
+
+    fn delay(&mut self) {
+        if let Some(fps_manager) = self.fps_manager.as_mut() {
+            fps_manager.delay();
+        }
+    }
+
+    fn clear(&mut self) {
+        self.canvas.set_draw_color(BLACK);
+        self.canvas.clear();
+        self.canvas.set_draw_color(WHITE);
+    }
+
+    fn draw_agent(&mut self, p: &Position, heading: f32) {
+        let canvas = &mut self.canvas;
+        let mut point_dir = heading;
+        let (sin, cos) = point_dir.sin_cos();
+        let x1 = cos.mul_add(6.0, p.x).round() as i16;
+        let y1 = sin.mul_add(6.0, p.y).round() as i16;
+        point_dir += 2.0 / 3.0 * PI;
+        let (sin, cos) = point_dir.sin_cos();
+        let x2 = cos.mul_add(5.0, p.x).round() as i16;
+        let y2 = sin.mul_add(5.0, p.y).round() as i16;
+        point_dir += 1.0 / 3.0 * PI;
+        let x3 = cos.mul_add(1.0, p.x).round() as i16;
+        let y3 = sin.mul_add(1.0, p.y).round() as i16;
+        point_dir += 1.0 / 3.0 * PI;
+        let (sin, cos) = point_dir.sin_cos();
+        let x4 = cos.mul_add(5.0, p.x).round() as i16;
+        let y4 = sin.mul_add(5.0, p.y).round() as i16;
+        canvas
+            .polygon(&[x1, x2, x3, x4], &[y1, y2, y3, y4], canvas.draw_color())
+            .expect("Error drawing to buffer");
+    }
+
+    fn draw_obstacle(&mut self, shape: &Shape) {
+        let canvas = &mut self.canvas;
+        let px = |v: f32| v.round() as i16;
+        match *shape {
+            Shape::Circle { x, y, radius } => canvas.filled_circle(px(x), px(y), px(radius), GREY),
+            Shape::Box { min, max } => {
+                canvas.box_(px(min.0), px(min.1), px(max.0), px(max.1), GREY)
+            }
+            Shape::Wall { from, to } => {
+                canvas.thick_line(px(from.0), px(from.1), px(to.0), px(to.1), 2, GREY)
+            }
+        }
+        .expect("Error drawing to buffer");
+    }
+
+    fn draw_target(&mut self, p: &Position) {
+        let canvas = &mut self.canvas;
+        canvas
+            .filled_circle(
+                p.x.round() as i16,
+                p.y.round() as i16,
+                4,
+                canvas.draw_color(),
+            )
+            .expect("Error drawing to buffer");
+    }
+}
+
+fn is_quit(event: &Event) -> bool {
+    matches!(
+        event,
+        Event::Quit { .. }
+            | Event::KeyDown {
+                keycode: Some(Keycode::Escape),
+                ..
+            }
+    )
+}
+
+// Run the world in a window at its tickrate until the window is closed or `stop` is reached
+// Pressing G runs without rendering or delay until the end of the current generation
+pub fn run(simulation: &mut Simulation, config: &Config, stop: Option<StopAfter>) {
+    let framerate_ratio = config.simulation.framerate_ratio;
+    let mut screen = Screen::open(
+        config.arena.width,
+        config.arena.height,
+        config.simulation.tickrate,
+    );
+    let obstacles = simulation::obstacle_shapes(&simulation.world);
+
+    let mut skip = false;
+    let mut skip_tick = 0;
+    let mut skip_start = Instant::now();
+    'running: loop {
+        screen.clear();
+
+        let ticks = simulation.ticks();
+        let generation = simulation.generation();
+
+        if skip && generation.ended(GenerationEvent::GenerationEnded) {
+            skip = false;
+            let elapsed = skip_start.elapsed().as_secs_f32();
+            let elapsed_ticks = ticks - skip_tick;
+            println!(
+                "Seconds: {elapsed:.2} - Ticks: {elapsed_ticks} - Tickrate: {:.2}",
+                elapsed_ticks as f32 / elapsed
+            );
+        }
+
+        if !skip && (framerate_ratio == 1 || ticks.is_multiple_of(framerate_ratio)) {
+            for shape in &obstacles {
+                screen.draw_obstacle(shape);
+            }
+            let position = simulation.world.read_storage::<Position>();
+            let velocity = simulation.world.read_storage::<Velocity>();
+            for (p, v) in (&position, (&velocity).maybe()).join() {
+                if let Some(v) = v {
+                    screen.draw_agent(p, v.heading);
+                } else {
+                    screen.draw_target(p);
+                }
+            }
+
+            screen.canvas.present();
+
+            skip = false;
+        }
+
+        if stop.is_some_and(|stop| stop.reached(ticks, &generation)) {
+            break;
+        }
+
+        if let Err(e) = simulation.step() {
+            eprintln!("Unable to save checkpoint: {e}");
+        }
+
+        if !skip {
+            for event in screen.event_pump.poll_iter() {
+                match event {
+                    ref e if is_quit(e) => break 'running,
+                    Event::KeyDown {
+                        keycode: Some(Keycode::G),
+                        ..
+                    } => {
+                        skip = true;
+                        skip_start = Instant::now();
+                        skip_tick = ticks;
+                    }
+                    _ => {}
+                }
+            }
+
+            screen.delay();
+        }
+    }
+}
+
+// Play back a recording at its tickrate, rendering every `framerate_ratio` frames
+pub fn replay(replay: &mut Replay, framerate_ratio: u64) -> io::Result<()> {
+    let mut screen = Screen::open(replay.width, replay.height, replay.tickrate);
+
+    while let Some(frame) = replay.next_frame()? {
+        if framerate_ratio == 1 || frame.tick % framerate_ratio == 0 {
+            screen.clear();
+            for shape in &replay.obstacles {
+                screen.draw_obstacle(shape);
+            }
+            for (p, heading) in &frame.agents {
+                screen.draw_agent(p, *heading);
+            }
+            for p in &frame.targets {
+                screen.draw_target(p);
+            }
+            screen.canvas.present();
+        }
+
+        if screen.event_pump.poll_iter().any(|e| is_quit(&e)) {
+            break;
+        }
+
+        screen.delay();
+    }
+
+    Ok(())
+}
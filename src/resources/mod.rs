@@ -1,16 +1,23 @@
 use crate::components::Position;
-use std::collections::HashSet;
+use crate::neural::Network;
+use rand_pcg::Pcg64Mcg;
+use std::collections::BTreeSet;
 
 pub struct DeltaTime(pub f32);
 
 pub struct MaxPos(pub Position);
 
-pub struct HitTargets(pub HashSet<specs::world::Index>);
+// Ordered so that targets are respawned in the same order on every run
+pub struct HitTargets(pub BTreeSet<specs::world::Index>);
 
 #[derive(Default)]
 pub struct Ticks(u64);
 
 impl Ticks {
+    pub fn new(ticks: u64) -> Self {
+        Self(ticks)
+    }
+
     pub fn inc(&mut self) {
         self.0 += 1;
     }
@@ -20,5 +27,190 @@ impl Ticks {
     }
 }
 
-// How frequently (in ticks) to generate a new population and reset scores
-pub struct ResetInterval(pub u64);
+// Where the generation is up to, systems that act on a generation's end run in one of the last three phases,
+// which only follow Evaluate on the tick that ends it
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Phase {
+    #[default]
+    Evaluate,
+    Select,
+    Breed,
+    Reset,
+}
+
+// What the current tick brings to an end, the end of a generation is also the end of its last episode
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum GenerationEvent {
+    EpisodeEnded,
+    GenerationEnded,
+}
+
+// The lifecycle of generations and their episodes, moved on a tick at a time by AdvanceGeneration
+// An episode lasts its full length unless it's ended early, so generations can vary in length
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Generation {
+    // generations ended before this one
+    number: u64,
+    // episodes of this generation ended before the current one
+    episode: u64,
+    // ticks into the current episode and generation, from 1 on their first tick
+    episode_ticks: u64,
+    ticks: u64,
+    phase: Phase,
+    ended: Option<GenerationEvent>,
+}
+
+impl Generation {
+    pub fn new(
+        number: u64,
+        episode: u64,
+        episode_ticks: u64,
+        ticks: u64,
+        ended: Option<GenerationEvent>,
+    ) -> Self {
+        Self {
+            number,
+            episode,
+            episode_ticks,
+            ticks,
+            phase: Phase::Evaluate,
+            ended,
+        }
+    }
+
+    pub fn number(&self) -> u64 {
+        self.number
+    }
+
+    // Generations ended so far, counting one that ends on the current tick
+    pub fn completed(&self) -> u64 {
+        self.number + self.ended(GenerationEvent::GenerationEnded) as u64
+    }
+
+    pub fn episode(&self) -> u64 {
+        self.episode
+    }
+
+    pub fn episode_ticks(&self) -> u64 {
+        self.episode_ticks
+    }
+
+    pub fn ticks(&self) -> u64 {
+        self.ticks
+    }
+
+    pub fn phase(&self) -> Phase {
+        self.phase
+    }
+
+    pub fn last_ended(&self) -> Option<GenerationEvent> {
+        self.ended
+    }
+
+    pub fn ended(&self, event: GenerationEvent) -> bool {
+        match event {
+            GenerationEvent::EpisodeEnded => self.ended.is_some(),
+            GenerationEvent::GenerationEnded => self.ended == Some(event),
+        }
+    }
+
+    // Move on a tick, starting whatever follows what ended on the last one
+    // The episode ends after `episode_length` ticks or sooner if `cleared`, and the generation with its
+    // last of `episodes`, which starts the Select phase
+    pub fn tick(&mut self, episode_length: u64, episodes: u64, cleared: bool) {
+        match self.ended.take() {
+            Some(GenerationEvent::GenerationEnded) => {
+                self.number += 1;
+                self.episode = 0;
+                self.episode_ticks = 0;
+                self.ticks = 0;
+            }
+            Some(GenerationEvent::EpisodeEnded) => {
+                self.episode += 1;
+                self.episode_ticks = 0;
+            }
+            None => {}
+        }
+        self.phase = Phase::Evaluate;
+        self.episode_ticks += 1;
+        self.ticks += 1;
+
+        if self.episode_ticks >= episode_length || cleared {
+            if self.episode + 1 >= episodes {
+                self.ended = Some(GenerationEvent::GenerationEnded);
+                self.phase = Phase::Select;
+            } else {
+                self.ended = Some(GenerationEvent::EpisodeEnded);
+            }
+        }
+    }
+
+    // Move on to `phase` if the generation is ending, otherwise it stays in Evaluate
+    pub fn enter(&mut self, phase: Phase) {
+        if self.phase != Phase::Evaluate {
+            self.phase = phase;
+        }
+    }
+}
+
+// Pairs of parents picked by Selection for Crossover to breed, in the order their children are created
+#[derive(Default)]
+pub struct Parents(pub Vec<(specs::Entity, specs::Entity)>);
+
+// The source of all randomness in the simulation, seeded so that a run can be reproduced exactly
+pub struct WorldRng(pub Pcg64Mcg);
+
+// Which island of an island model run a world is, and what it passes on at the end of each generation
+pub struct Island {
+    pub index: usize,
+    // the hits of every agent over the generation that just ended
+    pub scores: Vec<u32>,
+    // the generation's fittest networks, when it's time for them to migrate
+    pub emigrants: Vec<Network>,
+}
+
+impl Island {
+    pub fn new(index: usize) -> Self {
+        Self {
+            index,
+            scores: vec![],
+            emigrants: vec![],
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn episodes_and_generations_end_in_turn() {
+        let mut generation = Generation::default();
+        let mut ends = vec![];
+        for tick in 1..=12 {
+            // The second generation's first episode is cut short
+            generation.tick(3, 2, tick == 8);
+            if let Some(event) = generation.last_ended() {
+                ends.push((tick, event, generation.completed()));
+            }
+            assert_eq!(
+                generation.phase() == Phase::Select,
+                generation.ended(GenerationEvent::GenerationEnded)
+            );
+        }
+        use GenerationEvent::*;
+        assert_eq!(
+            ends,
+            vec![
+                (3, EpisodeEnded, 0),
+                (6, GenerationEnded, 1),
+                (8, EpisodeEnded, 1),
+                (11, GenerationEnded, 2),
+            ]
+        );
+        assert_eq!(generation.ticks(), 1);
+
+        generation.enter(Phase::Breed);
+        assert_eq!(generation.phase(), Phase::Evaluate);
+    }
+}
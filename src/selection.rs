@@ -0,0 +1,307 @@
+use crate::config::{SelectionConfig, SelectionMethod};
+use rand::distributions::{Distribution, WeightedIndex};
+use rand::seq::SliceRandom;
+use rand::{Rng, RngCore};
+
+// How a generation's parents are picked from its scores, the weighted sums of their fitness terms
+// Every strategy is run by the same Selection system, which pairs up the picks for Crossover
+pub trait SelectionStrategy: Send + Sync {
+    // `num_pairs` pairs of parents, as indices into `scores`
+    fn select(
+        &self,
+        rng: &mut dyn RngCore,
+        scores: &[f32],
+        num_pairs: usize,
+    ) -> Vec<(usize, usize)>;
+}
+
+pub fn from_config(config: &SelectionConfig) -> Box<dyn SelectionStrategy> {
+    match config.method {
+        SelectionMethod::Rank => Box::new(Rank),
+        SelectionMethod::Roulette => Box::new(Roulette),
+        SelectionMethod::Tournament => Box::new(Tournament {
+            size: config.tournament_size,
+            probability: config.tournament_probability,
+        }),
+        SelectionMethod::Truncation => Box::new(Truncation {
+            fraction: config.truncation_fraction,
+        }),
+        SelectionMethod::Sus => Box::new(StochasticUniversal),
+        SelectionMethod::Boltzmann => Box::new(Boltzmann {
+            temperature: config.temperature,
+        }),
+        // Scores are the crowded comparison order for NSGA-II (see Selection), which binary tournaments pick by
+        SelectionMethod::Nsga2 => Box::new(Tournament {
+            size: 2,
+            probability: 1.0,
+        }),
+    }
+}
+
+// Ranks each score in ascending order, with equal scores sharing a rank
+// i.e scores of [2, 4, 4, 8, 13] would rank [1, 2, 2, 3, 4]
+pub fn dense_ranks(scores: &[f32]) -> Vec<u32> {
+    let mut sorted = scores.to_vec();
+    sorted.sort_unstable_by(f32::total_cmp);
+    sorted.dedup();
+
+    scores
+        .iter()
+        .map(|s| sorted.binary_search_by(|x| x.total_cmp(s)).unwrap() as u32 + 1)
+        .collect()
+}
+
+// Scores shifted up so that none are negative, for the strategies that pick in proportion to them
+fn proportions(scores: &[f32]) -> Vec<f64> {
+    let min = scores.iter().copied().fold(0.0, f32::min);
+    scores.iter().map(|&s| f64::from(s - min)).collect()
+}
+
+// Each parent picked independently with a chance proportional to its weight, or all alike when no weight is positive
+fn weighted_pairs(rng: &mut dyn RngCore, weights: &[f64], num_pairs: usize) -> Vec<(usize, usize)> {
+    match WeightedIndex::new(weights) {
+        Ok(index) => (0..num_pairs)
+            .map(|_| (index.sample(rng), index.sample(rng)))
+            .collect(),
+        Err(_) => uniform_pairs(rng, weights.len(), num_pairs),
+    }
+}
+
+fn uniform_pairs(rng: &mut dyn RngCore, len: usize, num_pairs: usize) -> Vec<(usize, usize)> {
+    (0..num_pairs)
+        .map(|_| (rng.gen_range(0..len), rng.gen_range(0..len)))
+        .collect()
+}
+
+// Chance proportional to rank, so only the order of scores matters
+pub struct Rank;
+
+impl SelectionStrategy for Rank {
+    fn select(
+        &self,
+        rng: &mut dyn RngCore,
+        scores: &[f32],
+        num_pairs: usize,
+    ) -> Vec<(usize, usize)> {
+        let weights: Vec<f64> = dense_ranks(scores).into_iter().map(f64::from).collect();
+        weighted_pairs(rng, &weights, num_pairs)
+    }
+}
+
+// Chance proportional to score, shifted up first if any are negative
+pub struct Roulette;
+
+impl SelectionStrategy for Roulette {
+    fn select(
+        &self,
+        rng: &mut dyn RngCore,
+        scores: &[f32],
+        num_pairs: usize,
+    ) -> Vec<(usize, usize)> {
+        weighted_pairs(rng, &proportions(scores), num_pairs)
+    }
+}
+
+// Each parent is the winner of a tournament between `size` randomly picked agents
+// Contestants are ordered from the fittest, each winning with a chance of `probability` if none before it did
+// and the last winning if nobody else does
+pub struct Tournament {
+    pub size: usize,
+    pub probability: f32,
+}
+
+impl Tournament {
+    fn winner(&self, rng: &mut dyn RngCore, scores: &[f32]) -> usize {
+        let mut picked =
+            rand::seq::index::sample(rng, scores.len(), self.size.min(scores.len())).into_vec();
+        picked.sort_by(|&a, &b| scores[b].total_cmp(&scores[a]));
+
+        let last = picked.len() - 1;
+        let winner = (0..last)
+            .find(|_| rng.gen::<f32>() < self.probability)
+            .unwrap_or(last);
+        picked[winner]
+    }
+}
+
+impl SelectionStrategy for Tournament {
+    fn select(
+        &self,
+        rng: &mut dyn RngCore,
+        scores: &[f32],
+        num_pairs: usize,
+    ) -> Vec<(usize, usize)> {
+        (0..num_pairs)
+            .map(|_| (self.winner(rng, scores), self.winner(rng, scores)))
+            .collect()
+    }
+}
+
+// Parents are picked alike from the top `fraction` of scores, ties going to whichever comes first
+pub struct Truncation {
+    pub fraction: f32,
+}
+
+impl SelectionStrategy for Truncation {
+    fn select(
+        &self,
+        rng: &mut dyn RngCore,
+        scores: &[f32],
+        num_pairs: usize,
+    ) -> Vec<(usize, usize)> {
+        let mut sorted: Vec<usize> = (0..scores.len()).collect();
+        sorted.sort_by(|&a, &b| scores[b].total_cmp(&scores[a]));
+        let kept = ((scores.len() as f32 * self.fraction).ceil() as usize).clamp(1, scores.len());
+
+        uniform_pairs(rng, kept, num_pairs)
+            .into_iter()
+            .map(|(a, b)| (sorted[a], sorted[b]))
+            .collect()
+    }
+}
+
+// Stochastic universal sampling, a chance proportional to score like roulette, but with every parent
+// picked by evenly spaced pointers from a single spin so the picks can't stray far from what's expected
+pub struct StochasticUniversal;
+
+impl SelectionStrategy for StochasticUniversal {
+    fn select(
+        &self,
+        rng: &mut dyn RngCore,
+        scores: &[f32],
+        num_pairs: usize,
+    ) -> Vec<(usize, usize)> {
+        let weights = proportions(scores);
+        let total: f64 = weights.iter().sum();
+        if total <= 0.0 || num_pairs == 0 {
+            return uniform_pairs(rng, scores.len(), num_pairs);
+        }
+
+        let num_picks = num_pairs * 2;
+        let step = total / num_picks as f64;
+        let mut pointer = rng.gen::<f64>() * step;
+        let mut cumulative = 0.0;
+        let mut picks = Vec::with_capacity(num_picks);
+        for (i, &weight) in weights.iter().enumerate() {
+            cumulative += weight;
+            while pointer < cumulative && picks.len() < num_picks {
+                picks.push(i);
+                pointer += step;
+            }
+        }
+        // Rounding can leave the last pointer just past the end
+        while picks.len() < num_picks {
+            picks.push(scores.len() - 1);
+        }
+
+        // Picks come out in order, so shuffle them before pairing them up
+        picks.shuffle(rng);
+        picks.chunks_exact(2).map(|p| (p[0], p[1])).collect()
+    }
+}
+
+// Chance proportional to exp(score / temperature), a low temperature strongly favouring the highest scores
+// and a high one picking nearly alike
+pub struct Boltzmann {
+    pub temperature: f32,
+}
+
+impl SelectionStrategy for Boltzmann {
+    fn select(
+        &self,
+        rng: &mut dyn RngCore,
+        scores: &[f32],
+        num_pairs: usize,
+    ) -> Vec<(usize, usize)> {
+        // Relative to the highest score so that the weights can't overflow
+        let max = scores.iter().copied().fold(f32::MIN, f32::max);
+        let weights: Vec<f64> = scores
+            .iter()
+            .map(|&s| f64::from((s - max) / self.temperature).exp())
+            .collect();
+        weighted_pairs(rng, &weights, num_pairs)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::SeedableRng;
+    use rand_pcg::Pcg64Mcg;
+
+    const SCORES: [f32; 6] = [4.0, 9.0, 1.0, 7.0, 0.0, 3.0];
+
+    fn picks(strategy: &dyn SelectionStrategy, scores: &[f32]) -> Vec<usize> {
+        let mut rng = Pcg64Mcg::seed_from_u64(1);
+        strategy
+            .select(&mut rng, scores, 500)
+            .into_iter()
+            .flat_map(|(a, b)| [a, b])
+            .collect()
+    }
+
+    fn count(picks: &[usize], i: usize) -> usize {
+        picks.iter().filter(|&&p| p == i).count()
+    }
+
+    #[test]
+    fn dense_ranks_tie() {
+        assert_eq!(
+            dense_ranks(&[2.0, 4.0, 13.0, 4.0, 8.0]),
+            vec![1, 2, 4, 2, 3]
+        );
+    }
+
+    #[test]
+    fn strategies_favour_higher_scores() {
+        let strategies: [Box<dyn SelectionStrategy>; 6] = [
+            Box::new(Rank),
+            Box::new(Roulette),
+            Box::new(Tournament {
+                size: 3,
+                probability: 0.9,
+            }),
+            Box::new(Truncation { fraction: 0.5 }),
+            Box::new(StochasticUniversal),
+            Box::new(Boltzmann { temperature: 2.0 }),
+        ];
+        for strategy in &strategies {
+            let picks = picks(strategy.as_ref(), &SCORES);
+            assert_eq!(picks.len(), 1000);
+            assert!(count(&picks, 1) > count(&picks, 5));
+            assert!(count(&picks, 3) > count(&picks, 2));
+        }
+    }
+
+    #[test]
+    fn exact_picks() {
+        // Everyone in a certain tournament means the fittest always wins
+        let tournament = Tournament {
+            size: SCORES.len(),
+            probability: 1.0,
+        };
+        let picked = picks(&tournament, &SCORES);
+        assert_eq!(count(&picked, 1), picked.len());
+
+        // Truncation only ever picks from the top half
+        let picked = picks(&Truncation { fraction: 0.5 }, &SCORES);
+        assert!(picked.iter().all(|p| [1, 3, 0].contains(p)));
+
+        // Sampling is proportional to score within one pick per pointer
+        let picked = picks(&StochasticUniversal, &SCORES);
+        let total: f32 = SCORES.iter().sum();
+        for (i, &score) in SCORES.iter().enumerate() {
+            let expected = (1000.0 * score / total) as usize;
+            assert!(count(&picked, i).abs_diff(expected) <= 1);
+        }
+
+        // Negative scores are shifted up, leaving the lowest with no chance
+        let picked = picks(&Roulette, &[-2.0, -1.0, 5.0]);
+        assert_eq!(count(&picked, 0), 0);
+        assert!(count(&picked, 2) > count(&picked, 1));
+
+        // Without any scores roulette picks alike rather than failing
+        let picked = picks(&Roulette, &[0.0, 0.0, 0.0]);
+        assert!((0..3).all(|i| count(&picked, i) > 0));
+    }
+}
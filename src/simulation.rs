@@ -0,0 +1,826 @@
+use crate::checkpoint::{self, Checkpoint};
+use crate::components::{
+    Age, Agent, Behavior, Elite, EpisodeResults, Fitness, Force, MutationParams, Objectives,
+    Obstacle, Performance, Position, Score, Target, Velocity,
+};
+use crate::config::Config;
+use crate::hall_of_fame::HallOfFame;
+use crate::neural::Network;
+use crate::novelty::NoveltyArchive;
+use crate::obstacles::{self, Shape};
+use crate::recording::Recorder;
+use crate::resources::{
+    DeltaTime, Generation, HitTargets, MaxPos, Parents, Phase, Ticks, WorldRng,
+};
+use crate::stats::RollingStats;
+use crate::systems::{
+    apply_force::ApplyForce,
+    apply_velocity::ApplyVelocity,
+    collision_check::CollisionCheck,
+    control::Control,
+    crossover::Crossover,
+    end_episode::EndEpisode,
+    export_stats::ExportStats,
+    generation::{AdvanceGeneration, EnterPhase},
+    hall_of_fame::{Reinject, UpdateHallOfFame},
+    islands::Emigrate,
+    mutate::Mutate,
+    novelty::EvaluateNovelty,
+    print_stats::PrintStats,
+    reset_positions::ResetPositions,
+    reset_scores::ResetScores,
+    reset_velocities::ResetVelocities,
+    selection::Selection,
+    spawn_new_targets::SpawnNewTargets,
+    steady_state::{PrintRollingStats, SteadyState},
+    tick_counter::TickCounter,
+    track_behavior::TrackBehavior,
+    track_performance::TrackPerformance,
+    vision::Vision,
+};
+use rand::{
+    distributions::{Distribution, Uniform},
+    thread_rng, Rng, SeedableRng,
+};
+use rand_pcg::Pcg64Mcg;
+use specs::{prelude::*, World, WorldExt};
+use std::f32::consts::PI;
+use std::{collections::BTreeSet, io, time::Instant};
+
+// When a headless run should stop
+#[derive(Clone, Copy, Debug)]
+pub enum StopAfter {
+    Generations(u64),
+    Ticks(u64),
+}
+
+impl StopAfter {
+    // Whether a run that's at `generation` after `ticks` ticks should stop
+    pub fn reached(&self, ticks: u64, generation: &Generation) -> bool {
+        match *self {
+            StopAfter::Generations(generations) => generation.completed() >= generations,
+            StopAfter::Ticks(last_tick) => ticks >= last_tick,
+        }
+    }
+}
+
+// The configured seed, or a new random one if the config doesn't have one
+// Seeds are kept within TOML's integer range so that they can always be passed back in with `--seed`
+pub fn seed(config: &Config) -> u64 {
+    config
+        .simulation
+        .seed
+        .unwrap_or_else(|| thread_rng().gen_range(0..=i64::MAX as u64))
+}
+
+// Create a world with its resources and components registered, and none of its entities but the obstacles,
+// which come from the config and are always created first so that entities are stored in the same order
+// The world's config records the seed, so that checkpoints and the hall of fame know which run they're from
+pub fn create_empty_world(config: &Config, seed: u64) -> World {
+    let mut world = World::new();
+    world.insert(DeltaTime(1.0 / config.simulation.tickrate as f32));
+    world.insert(MaxPos(Position {
+        x: config.arena.width as f32,
+        y: config.arena.height as f32,
+    }));
+    world.insert(HitTargets(BTreeSet::<specs::world::Index>::new()));
+    world.insert(WorldRng(Pcg64Mcg::seed_from_u64(seed)));
+    world.insert(Ticks::default());
+    world.insert(Parents::default());
+    world.insert(Generation::default());
+    world.insert(HallOfFame::new(config.hall_of_fame.size));
+    world.insert(NoveltyArchive::new(config.novelty.archive_size));
+    world.insert(RollingStats::new(config.steady_state.window));
+    let mut config = config.clone();
+    config.simulation.seed = Some(seed);
+    world.insert(config);
+    world.register::<Agent>();
+    world.register::<Score>();
+    world.register::<Fitness>();
+    world.register::<Objectives>();
+    world.register::<Target>();
+    world.register::<Elite>();
+    world.register::<Position>();
+    world.register::<Velocity>();
+    world.register::<Force>();
+    world.register::<Performance>();
+    world.register::<Behavior>();
+    world.register::<Age>();
+    world.register::<EpisodeResults>();
+    world.register::<Obstacle>();
+
+    let shapes = world.read_resource::<Config>().obstacles.shapes.clone();
+    for shape in shapes {
+        world.create_entity().with(Obstacle { shape }).build();
+    }
+
+    world
+}
+
+// The shapes of every obstacle in the world
+pub fn obstacle_shapes(world: &World) -> Vec<Shape> {
+    world
+        .read_storage::<Obstacle>()
+        .join()
+        .map(|o| o.shape)
+        .collect()
+}
+
+// Create a world populated with randomly placed targets and agents with random networks
+pub fn create_world(config: &Config, seed: u64) -> World {
+    let mut world = create_empty_world(config, seed);
+
+    let networks = {
+        let rng = &mut world.write_resource::<WorldRng>().0;
+        (0..config.simulation.num_agents)
+            .map(|_| {
+                Network::random_with_activations(
+                    rng,
+                    &config.network.topology,
+                    &config.network.activations(),
+                )
+            })
+            .collect()
+    };
+    spawn_targets(&mut world);
+    spawn_agents(&mut world, networks);
+
+    world
+}
+
+// Place `simulation.num_targets` targets randomly in the world
+pub fn spawn_targets(world: &mut World) {
+    let shapes = obstacle_shapes(world);
+    let positions: Vec<Position> = {
+        let max = world.read_resource::<MaxPos>().0;
+        let num_targets = world.read_resource::<Config>().simulation.num_targets;
+        let rng = &mut world.write_resource::<WorldRng>().0;
+        let x_range = Uniform::from(0.0..max.x);
+        let y_range = Uniform::from(0.0..max.y);
+
+        (0..num_targets)
+            .map(|_| {
+                obstacles::place(&shapes, &max, || Position {
+                    x: x_range.sample(rng),
+                    y: y_range.sample(rng),
+                })
+            })
+            .collect()
+    };
+
+    for position in positions {
+        world.create_entity().with(Target).with(position).build();
+    }
+}
+
+// Create an agent for each network with a random position and velocity, and the configured mutation parameters
+pub fn spawn_agents(world: &mut World, networks: Vec<Network>) {
+    let mutation = {
+        let config = &world.read_resource::<Config>().mutation;
+        MutationParams {
+            rate: config.probability,
+            sigma: config.sigma,
+        }
+    };
+    let shapes = obstacle_shapes(world);
+    let placements: Vec<(Position, Velocity)> = {
+        let max = world.read_resource::<MaxPos>().0;
+        let config = world.read_resource::<Config>();
+        let (min_magnitude, max_magnitude) = config.velocity.initial_magnitude;
+        let rng = &mut world.write_resource::<WorldRng>().0;
+        let x_range = Uniform::from(0.0..max.x);
+        let y_range = Uniform::from(0.0..max.y);
+        let heading_range = Uniform::from(0.0..(2.0 * PI));
+        let magnitude_range = Uniform::from(min_magnitude..max_magnitude);
+
+        networks
+            .iter()
+            .map(|_| {
+                let position = obstacles::place(&shapes, &max, || Position {
+                    x: x_range.sample(rng),
+                    y: y_range.sample(rng),
+                });
+                let velocity = Velocity {
+                    heading: heading_range.sample(rng),
+                    magnitude: magnitude_range.sample(rng),
+                };
+                (position, velocity)
+            })
+            .collect()
+    };
+
+    let entities: Vec<Entity> = networks
+        .into_iter()
+        .zip(placements)
+        .map(|(network, (position, velocity))| {
+            spawn_agent(world, network, mutation, position, velocity, Score::new())
+        })
+        .collect();
+
+    // Steady-state agents start at random ages, so that they don't all expire at once
+    let config = world.read_resource::<Config>().clone();
+    if config.steady_state.enabled {
+        let lifetime = config
+            .steady_state
+            .lifetime_ticks(config.simulation.tickrate);
+        let (mut ages, mut rng) = world.system_data::<(WriteStorage<Age>, WriteExpect<WorldRng>)>();
+        for entity in entities {
+            let ticks = rng.0.gen_range(0..lifetime);
+            ages.insert(entity, Age { ticks })
+                .expect("Unable to set age");
+        }
+    }
+}
+
+// Create a single agent with all of the components it needs to take part in the simulation
+pub fn spawn_agent(
+    world: &mut World,
+    network: Network,
+    mutation: MutationParams,
+    position: Position,
+    velocity: Velocity,
+    score: Score,
+) -> Entity {
+    world
+        .create_entity()
+        .with(Agent {
+            inputs: None,
+            network,
+            mutation,
+        })
+        .with(score)
+        .with(position)
+        .with(velocity)
+        .with(Force::default())
+        .with(Performance::default())
+        .with(Behavior::default())
+        .with(Age::default())
+        .with(EpisodeResults::default())
+        .build()
+}
+
+// Make sure loaded networks can drive an agent, which needs a rotation and translation output
+pub fn check_networks(networks: &[Network]) -> Result<(), String> {
+    match networks.iter().position(|n| n.output_size() != 2) {
+        Some(i) => Err(format!(
+            "network {i} has {} outputs, agents need 2",
+            networks[i].output_size()
+        )),
+        None => Ok(()),
+    }
+}
+
+// The networks of every agent in the world
+pub fn networks(world: &World) -> Vec<Network> {
+    world
+        .read_storage::<Agent>()
+        .join()
+        .map(|agent| agent.network.clone())
+        .collect()
+}
+
+// The simulation pipeline shared by every mode, plus selection and breeding when `evolve` is set,
+// which happen a generation at a time or, with `steady_state.enabled`, an agent at a time
+// When given a recorder, the state of the world is recorded at the end of each tick
+// When given a stats exporter, it is run once selection has ranked the generation
+fn create_dispatcher<'a, 'b>(
+    config: &Config,
+    evolve: bool,
+    recorder: Option<Recorder>,
+    export_stats: Option<ExportStats>,
+) -> Dispatcher<'a, 'b> {
+    let mut builder = DispatcherBuilder::new()
+        .with(TickCounter, "tick_counter", &[])
+        .with(Vision, "vision", &[])
+        .with(Control::default(), "control", &["vision"])
+        .with(ApplyForce, "apply_force", &["control"])
+        .with(ApplyVelocity, "apply_velocity", &["apply_force"])
+        .with(CollisionCheck, "collision_check", &["apply_velocity"])
+        .with(SpawnNewTargets, "spawn_new_targets", &["collision_check"])
+        .with(
+            AdvanceGeneration,
+            "advance_generation",
+            &["spawn_new_targets"],
+        );
+
+    let steady_state = evolve && config.steady_state.enabled;
+    if steady_state {
+        builder = builder
+            .with(
+                TrackPerformance,
+                "track_performance",
+                &["advance_generation"],
+            )
+            .with(SteadyState, "steady_state", &["track_performance"])
+            .with(PrintRollingStats, "print_rolling_stats", &["steady_state"]);
+    } else if evolve {
+        builder = builder
+            // Ahead of reset_velocities and reset_positions through the phases, so that they see how the episode ended
+            .with(
+                TrackPerformance,
+                "track_performance",
+                &["advance_generation"],
+            )
+            .with(TrackBehavior, "track_behavior", &["advance_generation"])
+            .with(EndEpisode, "end_episode", &["track_performance"])
+            .with(PrintStats, "print_stats", &["end_episode"])
+            .with(
+                EvaluateNovelty,
+                "evaluate_novelty",
+                &["end_episode", "track_behavior"],
+            )
+            .with(
+                Selection::from_config(config),
+                "selection",
+                &["evaluate_novelty"],
+            );
+        // Every system reading the selection phase, which has to be over before breeding starts
+        let mut selecting = vec![
+            "print_stats",
+            "selection",
+            "update_hall_of_fame",
+            "emigrate",
+        ];
+        if let Some(export_stats) = export_stats {
+            builder = builder.with(export_stats, "export_stats", &["selection"]);
+            selecting.push("export_stats");
+        }
+        builder = builder
+            .with(UpdateHallOfFame, "update_hall_of_fame", &["selection"])
+            .with(Emigrate, "emigrate", &["selection"])
+            .with(EnterPhase(Phase::Breed), "breed", &selecting)
+            .with(Crossover, "crossover", &["breed"])
+            .with(Mutate, "mutate", &["crossover"])
+            .with(Reinject, "reinject", &["mutate"])
+            .with(
+                EnterPhase(Phase::Reset),
+                "reset",
+                &["crossover", "mutate", "reinject"],
+            )
+            .with(ResetScores, "reset_scores", &["reset"])
+            .with(ResetPositions, "reset_positions", &["reset"])
+            .with(ResetVelocities, "reset_velocities", &["reset"]);
+    }
+
+    if let Some(recorder) = recorder {
+        let deps: &[&str] = if steady_state {
+            &["spawn_new_targets", "steady_state"]
+        } else if evolve {
+            &["spawn_new_targets", "reset_positions", "reset_velocities"]
+        } else {
+            &["spawn_new_targets"]
+        };
+        builder = builder.with(recorder, "recorder", deps);
+    }
+
+    builder.build()
+}
+
+// Dispatch ticks back to back until the world reaches `last_tick`
+fn run_until(world: &mut World, dispatcher: &mut Dispatcher, last_tick: u64) {
+    while world.read_resource::<Ticks>().get() < last_tick {
+        dispatcher.dispatch(world);
+        world.maintain();
+    }
+}
+
+// A world together with the dispatcher that runs it
+pub struct Simulation {
+    pub world: World,
+    pub dispatcher: Dispatcher<'static, 'static>,
+}
+
+// Configures how a simulation's world is populated and which systems run on it
+// By default the world is filled with random networks and the population evolves
+pub struct SimulationBuilder {
+    config: Config,
+    seed: Option<u64>,
+    networks: Option<Vec<Network>>,
+    checkpoint: Option<Checkpoint>,
+    evolve: bool,
+    recorder: Option<Recorder>,
+    export_stats: Option<ExportStats>,
+}
+
+impl SimulationBuilder {
+    pub fn new(config: &Config) -> Self {
+        Self {
+            config: config.clone(),
+            seed: None,
+            networks: None,
+            checkpoint: None,
+            evolve: true,
+            recorder: None,
+            export_stats: None,
+        }
+    }
+
+    // Seed for the world's RNG, otherwise the configured seed or a random one
+    pub fn seed(mut self, seed: u64) -> Self {
+        self.seed = Some(seed);
+        self
+    }
+
+    // Populate the world with these networks instead of random ones
+    pub fn networks(mut self, networks: Vec<Network>) -> Self {
+        self.networks = Some(networks);
+        self
+    }
+
+    // Carry on from a checkpoint, whose config, RNG state and entities take the place of the builder's
+    pub fn resume(mut self, checkpoint: Checkpoint) -> Self {
+        self.checkpoint = Some(checkpoint);
+        self
+    }
+
+    // Whether to run selection and breeding at the end of each generation
+    pub fn evolve(mut self, evolve: bool) -> Self {
+        self.evolve = evolve;
+        self
+    }
+
+    pub fn recorder(mut self, recorder: Recorder) -> Self {
+        self.recorder = Some(recorder);
+        self
+    }
+
+    pub fn export_stats(mut self, export_stats: ExportStats) -> Self {
+        self.export_stats = Some(export_stats);
+        self
+    }
+
+    pub fn build(self) -> Simulation {
+        let world = match (self.checkpoint, self.networks) {
+            (Some(checkpoint), _) => checkpoint.into_world(),
+            (None, networks) => {
+                let seed = self.seed.unwrap_or_else(|| seed(&self.config));
+                match networks {
+                    Some(networks) => {
+                        let mut world = create_empty_world(&self.config, seed);
+                        spawn_targets(&mut world);
+                        spawn_agents(&mut world, networks);
+                        world
+                    }
+                    None => create_world(&self.config, seed),
+                }
+            }
+        };
+
+        // A resumed run goes by the checkpoint's config rather than the builder's
+        let dispatcher = create_dispatcher(
+            &world.read_resource::<Config>(),
+            self.evolve,
+            self.recorder,
+            self.export_stats,
+        );
+        Simulation { world, dispatcher }
+    }
+}
+
+impl Simulation {
+    pub fn builder(config: &Config) -> SimulationBuilder {
+        SimulationBuilder::new(config)
+    }
+
+    pub fn ticks(&self) -> u64 {
+        self.world.read_resource::<Ticks>().get()
+    }
+
+    // Ticks in a generation whose episodes all run their full length
+    pub fn reset_interval(&self) -> u64 {
+        self.world.read_resource::<Config>().reset_interval()
+    }
+
+    pub fn generation(&self) -> Generation {
+        *self.world.read_resource::<Generation>()
+    }
+
+    // The networks of every agent in the world
+    pub fn networks(&self) -> Vec<Network> {
+        networks(&self.world)
+    }
+
+    // Run a single tick, then save a checkpoint if one is due
+    // An error means only the checkpoint failed, the tick itself has run
+    pub fn step(&mut self) -> io::Result<()> {
+        self.dispatcher.dispatch(&self.world);
+        self.world.maintain();
+        checkpoint::save_if_due(&self.world)
+    }
+
+    // Run ticks back to back with no rendering or frame delay until `stop` is reached
+    pub fn run_headless(&mut self, stop: StopAfter) {
+        let start = Instant::now();
+        let start_tick = self.ticks();
+
+        while !stop.reached(self.ticks(), &self.generation()) {
+            if let Err(e) = self.step() {
+                eprintln!("Unable to save checkpoint: {e}");
+            }
+        }
+
+        let elapsed = start.elapsed().as_secs_f32();
+        let elapsed_ticks = self.ticks() - start_tick;
+        println!(
+            "Seconds: {elapsed:.2} - Ticks: {elapsed_ticks} - Tickrate: {:.2}",
+            elapsed_ticks as f32 / elapsed
+        );
+    }
+}
+
+// Score a network alone in each of `scenarios` fixed worlds for one population lifetime
+// Scenario `n` is always seeded with `n`, so scores are comparable between networks
+pub fn evaluate(config: &Config, network: &Network, scenarios: u64) -> Vec<u32> {
+    (0..scenarios)
+        .map(|scenario| {
+            let mut world = create_empty_world(config, scenario);
+            spawn_targets(&mut world);
+            spawn_agents(&mut world, vec![network.clone()]);
+
+            let mut dispatcher = create_dispatcher(config, false, None, None);
+            run_until(&mut world, &mut dispatcher, config.episode_ticks());
+
+            let scores = world.read_storage::<Score>();
+            scores.join().map(|s| s.score()).sum()
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::resources::GenerationEvent;
+
+    // The defaults with one-second generations at 30 ticks a second, under `extra` (more TOML)
+    fn test_config(extra: &str) -> Config {
+        let mut table: toml::Table = extra.parse().unwrap();
+        let simulation = table
+            .entry("simulation")
+            .or_insert_with(|| toml::Value::Table(toml::Table::new()))
+            .as_table_mut()
+            .unwrap();
+        simulation
+            .entry("tickrate")
+            .or_insert(toml::Value::Integer(30));
+        simulation
+            .entry("population_lifetime_seconds")
+            .or_insert(toml::Value::Integer(1));
+        toml::to_string(&table).unwrap().parse().unwrap()
+    }
+
+    // A simulation seeded with 5, run for `generations` generations
+    fn run(config: &Config, generations: u64) -> Simulation {
+        let mut simulation = Simulation::builder(config).seed(5).build();
+        let last_tick = generations * simulation.reset_interval();
+        run_to(&mut simulation, last_tick);
+        simulation
+    }
+
+    fn run_to(simulation: &mut Simulation, last_tick: u64) {
+        run_until(&mut simulation.world, &mut simulation.dispatcher, last_tick);
+    }
+
+    fn to_text(networks: &[Network]) -> Vec<u8> {
+        let mut buf = vec![];
+        for n in networks {
+            n.write_text(&mut buf).unwrap();
+        }
+        buf
+    }
+
+    #[test]
+    fn seeded_runs_are_reproducible() {
+        let config = test_config("");
+        let networks = |seed| {
+            let mut simulation = Simulation::builder(&config).seed(seed).build();
+            let last_tick = 3 * simulation.reset_interval();
+            run_to(&mut simulation, last_tick);
+            to_text(&simulation.networks())
+        };
+        assert_eq!(networks(7), networks(7));
+        assert_ne!(networks(7), networks(8));
+    }
+
+    #[test]
+    fn nsga2_ranks_every_agent() {
+        let config = test_config("[selection]\nmethod = \"nsga2\"\nelites = 2\n");
+        let simulation = run(&config, 2);
+
+        let objectives = simulation.world.read_storage::<Objectives>();
+        assert_eq!(objectives.join().count(), config.simulation.num_agents);
+        assert!(objectives.join().any(|o| o.rank.front == 0));
+        assert!(objectives.join().all(|o| o.values.len() == 2));
+    }
+
+    #[test]
+    fn novelty_search_archives_behaviours() {
+        let config = test_config("[fitness]\nhits = 0.0\nnovelty = 1.0\n\n[novelty]\nsamples = 3\narchive_per_generation = 4\n");
+        let mut simulation = run(&config, 0);
+        let interval = simulation.reset_interval();
+        run_to(&mut simulation, interval - 1);
+        // Two of the three samples, each a position as 4 values
+        let behaviors = simulation.world.read_storage::<Behavior>();
+        assert!(behaviors.join().all(|b| b.descriptor.len() == 8));
+        drop(behaviors);
+
+        run_to(&mut simulation, 2 * interval);
+        let archive = simulation.world.read_resource::<NoveltyArchive>();
+        assert_eq!(archive.descriptors().len(), 8);
+        assert!(archive.descriptors().iter().all(|d| d.len() == 12));
+        // Fitness goes by novelty alone, so the agents don't all tie
+        let fitnesses = simulation.world.read_storage::<Fitness>();
+        assert!(fitnesses.join().any(|f| f.fitness > 1));
+    }
+
+    #[test]
+    fn steady_state_replaces_agents_one_at_a_time() {
+        let config = test_config("[steady_state]\nenabled = true\nlifetime_seconds = 1\n");
+        let mut simulation = run(&config, 0);
+        let before = simulation.networks();
+        run_to(&mut simulation, 10);
+
+        // Staggered ages mean that only some agents have been replaced so far
+        let after = simulation.networks();
+        let replaced = before.iter().zip(&after).filter(|(a, b)| a != b).count();
+        let births = simulation.world.read_resource::<RollingStats>().births;
+        assert!(births > 0 && (births as usize) < config.simulation.num_agents);
+        assert_eq!(replaced as u64, births);
+
+        run_to(&mut simulation, 45);
+        let ages = simulation.world.read_storage::<Age>();
+        assert!(ages.join().all(|a| a.ticks < 30));
+        assert_eq!(
+            simulation.world.read_resource::<RollingStats>().len() as u64,
+            simulation.world.read_resource::<RollingStats>().births
+        );
+    }
+
+    #[test]
+    fn generations_span_every_episode() {
+        let config = test_config("[evaluation]\nepisodes = 3\naggregate = \"min\"\n");
+        let mut simulation = run(&config, 0);
+        assert_eq!(simulation.reset_interval(), 90);
+        let before = simulation.networks();
+        run_to(&mut simulation, 60);
+
+        // Two episodes have ended, each starting the agents' scores over, and selection hasn't run yet
+        let results = simulation.world.read_storage::<EpisodeResults>();
+        assert!(results.join().all(|r| r.episodes.len() == 2));
+        let scores = simulation.world.read_storage::<Score>();
+        assert!(scores.join().all(|s| s.score() == 0));
+        assert_eq!(simulation.networks(), before);
+        drop((results, scores));
+
+        run_to(&mut simulation, 90);
+        assert_ne!(simulation.networks(), before);
+        let results = simulation.world.read_storage::<EpisodeResults>();
+        assert!(results.join().all(|r| r.episodes.is_empty()));
+    }
+
+    #[test]
+    fn generations_end_early_once_targets_are_gone() {
+        let config = test_config("[arena]\nwidth = 20\nheight = 20\n\n[simulation]\nnum_targets = 3\nrespawn_targets = false\n");
+        let mut simulation = run(&config, 0);
+        let stop = StopAfter::Generations(2);
+        while !stop.reached(simulation.ticks(), &simulation.generation()) {
+            simulation.step().unwrap();
+            let generation = simulation.generation();
+            let (targets, positions) = simulation
+                .world
+                .system_data::<(ReadStorage<Target>, ReadStorage<Position>)>();
+            let remaining = (&targets, &positions).join().count();
+            if generation.ended(GenerationEvent::GenerationEnded) {
+                // Every target was brought back for the next generation
+                assert_eq!(remaining, 3);
+            } else {
+                assert!(remaining > 0 && remaining <= 3);
+            }
+        }
+        // With agents crowded around so few targets, neither generation ran its full length
+        assert!(simulation.ticks() < 2 * simulation.reset_interval());
+        assert_eq!(simulation.generation().number(), 1);
+    }
+
+    #[test]
+    fn agents_stay_out_of_obstacles() {
+        let config = test_config(
+            r#"
+[arena]
+width = 200
+height = 200
+
+[network]
+topology = [10, 2]
+
+[obstacles]
+collision = "slide"
+sense = true
+
+[[obstacles.shapes]]
+shape = "box"
+min = [40.0, 40.0]
+max = [160.0, 80.0]
+
+[[obstacles.shapes]]
+shape = "circle"
+x = 100.0
+y = 140.0
+radius = 30.0
+
+[[obstacles.shapes]]
+shape = "wall"
+from = [0.0, 100.0]
+to = [200.0, 100.0]
+"#,
+        );
+        // The obstacles survive being written into a checkpoint's config
+        let written: Config = toml::to_string(&config).unwrap().parse().unwrap();
+        assert_eq!(written.obstacles.shapes, config.obstacles.shapes);
+
+        let mut simulation = run(&config, 0);
+        let shapes = obstacle_shapes(&simulation.world);
+        assert_eq!(shapes.len(), 3);
+        let max = simulation.world.read_resource::<MaxPos>().0;
+        let agent_positions = |world: &World| -> Vec<Position> {
+            let (agents, positions) =
+                world.system_data::<(ReadStorage<Agent>, ReadStorage<Position>)>();
+            (&agents, &positions).join().map(|(_, &p)| p).collect()
+        };
+
+        for _ in 0..29 {
+            let before = agent_positions(&simulation.world);
+            simulation.step().unwrap();
+            let after = agent_positions(&simulation.world);
+            let positions = simulation.world.read_storage::<Position>();
+            assert!(positions
+                .join()
+                .all(|p| !obstacles::inside(&shapes, p, &max)));
+            // Nothing goes through the wall across the middle, only around it where the arena wraps
+            for (b, a) in before.iter().zip(&after) {
+                if (b.y < 100.0) != (a.y < 100.0) {
+                    assert!((a.y - b.y).abs() > 100.0);
+                }
+            }
+            // Targets and obstacles each have a channel of 5 receptors
+            let agents = simulation.world.read_storage::<Agent>();
+            assert!(agents
+                .join()
+                .all(|a| a.inputs.as_ref().is_some_and(|i| i.len() == 10)));
+        }
+    }
+
+    #[test]
+    fn hall_of_fame_keeps_the_best_of_a_generation() {
+        let config = test_config("[hall_of_fame]\nsize = 4\nreinject = 2\n");
+        let mut simulation = run(&config, 0);
+        let interval = simulation.reset_interval();
+        run_to(&mut simulation, interval - 1);
+        let before = simulation.networks();
+        let best = simulation
+            .world
+            .read_storage::<Score>()
+            .join()
+            .map(|s| s.score())
+            .max();
+        run_to(&mut simulation, interval);
+
+        let hall = simulation.world.read_resource::<HallOfFame>();
+        assert_eq!(hall.members().len(), 4);
+        assert_eq!(Some(hall.members()[0].score), best);
+        assert!(hall
+            .members()
+            .iter()
+            .all(|m| m.generation == 0 && m.seed == 5));
+        assert!(hall.members().iter().all(|m| before.contains(&m.network)));
+        // Members were copied into the new generation
+        let after = simulation.networks();
+        assert!(hall.members().iter().any(|m| after.contains(&m.network)));
+    }
+
+    #[test]
+    fn elites_carry_over_unchanged() {
+        let config = test_config("[selection]\nelites = 3\n");
+        let mut simulation = run(&config, 0);
+        let interval = simulation.reset_interval();
+        run_to(&mut simulation, interval - 1);
+        let before = simulation.networks();
+        run_to(&mut simulation, interval);
+
+        let (agents, elites, fitnesses) =
+            simulation
+                .world
+                .system_data::<(ReadStorage<Agent>, ReadStorage<Elite>, ReadStorage<Fitness>)>();
+        assert_eq!(elites.join().count(), 3);
+        let max_offspring = (&fitnesses, !&elites).join().map(|(f, _)| f.fitness).max();
+        for (i, (agent, elite, fitness)) in (&agents, elites.maybe(), &fitnesses).join().enumerate()
+        {
+            if elite.is_some() {
+                assert!(Some(fitness.fitness) >= max_offspring);
+                assert_eq!(
+                    to_text(&before[i..=i]),
+                    to_text(std::slice::from_ref(&agent.network))
+                );
+            }
+        }
+    }
+}
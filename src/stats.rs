@@ -0,0 +1,139 @@
+use serde::Serialize;
+use std::collections::VecDeque;
+
+// Summary of the scores of a population at the end of a generation
+#[derive(Clone, Debug, Default, Serialize)]
+pub struct GenerationStats {
+    pub avg: f32,
+    pub min: u32,
+    pub max: u32,
+    pub total: u32,
+    pub median: f32,
+    // population (not sample) standard deviation
+    pub std_dev: f32,
+}
+
+impl GenerationStats {
+    pub fn from_scores(scores: &[u32]) -> Self {
+        let values: Vec<f32> = scores.iter().map(|&s| s as f32).collect();
+        let summary = Summary::from_values(&values);
+
+        Self {
+            avg: summary.mean,
+            min: scores.iter().copied().min().unwrap_or(0),
+            max: scores.iter().copied().max().unwrap_or(0),
+            total: scores.iter().sum(),
+            median: summary.median,
+            std_dev: summary.std_dev,
+        }
+    }
+}
+
+// Distribution of some value across a population
+#[derive(Clone, Debug, Default, Serialize)]
+pub struct Summary {
+    pub mean: f32,
+    pub median: f32,
+    pub min: f32,
+    pub max: f32,
+    // population (not sample) standard deviation
+    pub std_dev: f32,
+}
+
+impl Summary {
+    pub fn from_values(values: &[f32]) -> Self {
+        if values.is_empty() {
+            return Self::default();
+        }
+
+        let mut sorted = values.to_vec();
+        sorted.sort_unstable_by(f32::total_cmp);
+
+        let num = sorted.len();
+        let mean = sorted.iter().sum::<f32>() / num as f32;
+        let median = if num.is_multiple_of(2) {
+            (sorted[num / 2 - 1] + sorted[num / 2]) / 2.0
+        } else {
+            sorted[num / 2]
+        };
+        let variance = sorted.iter().map(|&v| (v - mean).powi(2)).sum::<f32>() / num as f32;
+
+        Self {
+            mean,
+            median,
+            min: sorted[0],
+            max: sorted[num - 1],
+            std_dev: variance.sqrt(),
+        }
+    }
+}
+
+// Scores of the most recent lifetimes in a steady-state run, which has no generations to summarise
+#[derive(Clone, Debug, Default)]
+pub struct RollingStats {
+    window: usize,
+    // oldest first
+    scores: VecDeque<u32>,
+    // agents bred so far
+    pub births: u64,
+}
+
+impl RollingStats {
+    pub fn new(window: usize) -> Self {
+        Self {
+            window,
+            scores: VecDeque::new(),
+            births: 0,
+        }
+    }
+
+    // The score of an agent whose life just ended, dropping the oldest once the window is full
+    pub fn record(&mut self, score: u32) {
+        if self.scores.len() == self.window {
+            self.scores.pop_front();
+        }
+        self.scores.push_back(score);
+    }
+
+    pub fn len(&self) -> usize {
+        self.scores.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.scores.is_empty()
+    }
+
+    pub fn stats(&self) -> GenerationStats {
+        GenerationStats::from_scores(&self.scores.iter().copied().collect::<Vec<u32>>())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn from_scores() {
+        let stats = GenerationStats::from_scores(&[4, 2, 13, 8, 4]);
+        assert_eq!(stats.total, 31);
+        assert_eq!(stats.min, 2);
+        assert_eq!(stats.max, 13);
+        assert!((stats.avg - 6.2).abs() < f32::EPSILON);
+        assert!((stats.median - 4.0).abs() < f32::EPSILON);
+        assert!((stats.std_dev - 15.36f32.sqrt()).abs() < 1e-5);
+
+        let stats = GenerationStats::from_scores(&[1, 2, 3, 4]);
+        assert!((stats.median - 2.5).abs() < f32::EPSILON);
+    }
+
+    #[test]
+    fn rolling_window() {
+        let mut rolling = RollingStats::new(3);
+        for score in [9, 1, 2, 3] {
+            rolling.record(score);
+        }
+        assert_eq!(rolling.len(), 3);
+        assert_eq!(rolling.stats().max, 3);
+        assert_eq!(rolling.stats().total, 6);
+    }
+}
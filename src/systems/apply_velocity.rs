@@ -1,4 +1,6 @@
-use crate::components::{Position, Velocity};
+use crate::components::{Obstacle, Position, Velocity};
+use crate::config::Config;
+use crate::obstacles::{self, Shape};
 use crate::resources::{DeltaTime, MaxPos};
 use specs::{prelude::*, ReadExpect, ReadStorage, System, WriteStorage};
 
@@ -10,15 +12,25 @@ impl<'a> System<'a> for ApplyVelocity {
         ReadExpect<'a, MaxPos>,
         WriteStorage<'a, Position>,
         ReadStorage<'a, Velocity>,
+        ReadStorage<'a, Obstacle>,
+        ReadExpect<'a, Config>,
     );
 
-    fn run(&mut self, (delta, max, mut position, velocity): Self::SystemData) {
+    fn run(&mut self, (delta, max, mut position, velocity, obstacles, config): Self::SystemData) {
         let delta = delta.0;
         let max = max.0;
+        let shapes: Vec<Shape> = obstacles.join().map(|o| o.shape).collect();
         for (pos, vel) in (&mut position, &velocity).join() {
             let (sin, cos) = vel.heading.sin_cos();
-            pos.x = (cos.mul_add(vel.magnitude * delta, pos.x)).rem_euclid(max.x);
-            pos.y = (sin.mul_add(vel.magnitude * delta, pos.y)).rem_euclid(max.y);
+            let mut next = Position {
+                x: cos.mul_add(vel.magnitude * delta, pos.x),
+                y: sin.mul_add(vel.magnitude * delta, pos.y),
+            };
+            if !shapes.is_empty() {
+                next = obstacles::deflect(&shapes, pos, &next, &max, config.obstacles.collision);
+            }
+            pos.x = next.x.rem_euclid(max.x);
+            pos.y = next.y.rem_euclid(max.y);
         }
     }
 }
@@ -1,7 +1,16 @@
 use crate::components::{Agent, Force};
+use crate::neural::Workspace;
 use specs::{prelude::*, ReadStorage, System, WriteStorage};
 
-pub struct Control;
+#[derive(Default)]
+pub struct Control {
+    workspace: Workspace,
+}
+
+// Map an output from the network's output range onto [-10, 10]
+fn to_force(output: f32, (min, max): (f32, f32)) -> f32 {
+    ((output.clamp(min, max) - min) / (max - min) - 0.5) * 20.0
+}
 
 impl<'a> System<'a> for Control {
     type SystemData = (ReadStorage<'a, Agent>, WriteStorage<'a, Force>);
@@ -9,22 +18,32 @@ impl<'a> System<'a> for Control {
     fn run(&mut self, (agents, mut forces): Self::SystemData) {
         for (agent, force) in (&agents, &mut forces).join() {
             if let Some(inputs) = agent.inputs.as_ref() {
-                let outputs = agent.network.propagate(inputs);
+                let outputs = agent.network.propagate_batch(inputs, &mut self.workspace);
                 if outputs.len() == 2 {
-                    // if neuron had no output, do nothing
-                    // otherwise, clamp output to (0, 1] and transform to (-10, 10]
-                    force.rotation = if outputs[0] > f32::EPSILON {
-                        (outputs[0].min(1.0) - 0.5) * 20.0
-                    } else {
-                        0.0
-                    };
-                    force.translation = if outputs[1] > f32::EPSILON {
-                        (outputs[1].min(1.0) - 0.5) * 20.0
-                    } else {
-                        0.0
-                    };
+                    let range = agent.network.output_activation().range();
+                    force.rotation = to_force(outputs[0], range);
+                    force.translation = to_force(outputs[1], range);
                 }
             }
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::neural::Activation;
+
+    #[test]
+    fn outputs_span_the_whole_force_range() {
+        let relu = Activation::Relu.range();
+        assert_eq!(to_force(0.0, relu), -10.0);
+        assert_eq!(to_force(0.5, relu), 0.0);
+        assert_eq!(to_force(1.0, relu), 10.0);
+        assert_eq!(to_force(3.0, relu), 10.0);
+
+        let tanh = Activation::Tanh.range();
+        assert_eq!(to_force(0.0, tanh), 0.0);
+        assert_eq!(to_force(-1.0, tanh), -10.0);
+    }
+}
@@ -1,51 +1,96 @@
-use crate::components::{Agent, Fitness};
+use crate::components::{Agent, Elite, Fitness, MutationParams};
+use crate::config::{Config, CrossoverConfig, CrossoverOperator};
 use crate::neural::Network;
-use crate::resources::{ResetInterval, Ticks};
-use rand::{seq::SliceRandom, thread_rng};
+use crate::resources::{Generation, Parents, Phase, WorldRng};
+use rand::Rng;
 use specs::{prelude::*, ReadExpect, System, WriteStorage};
+use std::cmp::Reverse;
+use std::collections::HashMap;
 
-struct NetworkFitness {
+// What a parent passes on, copied out before any agent is overwritten by its child
+struct Genome {
     network: Network,
-    fitness: u32,
+    mutation: MutationParams,
+}
+
+// A child of `a` and `b` by the configured operator
+pub fn cross<R: Rng + ?Sized>(
+    rng: &mut R,
+    a: &Network,
+    b: &Network,
+    crossover: &CrossoverConfig,
+) -> Network {
+    match crossover.operator {
+        CrossoverOperator::Uniform => a.crossover_uniform(rng, b),
+        CrossoverOperator::KPoint => a.crossover_k_point(rng, b, crossover.points),
+        CrossoverOperator::Blend => a.crossover_blend(rng, b, crossover.alpha),
+        CrossoverOperator::Sbx => a.crossover_sbx(rng, b, crossover.eta),
+        CrossoverOperator::Neuron => a.crossover_neuron(rng, b),
+    }
 }
 
 pub struct Crossover;
 
 impl<'a> System<'a> for Crossover {
     type SystemData = (
+        Entities<'a>,
         WriteStorage<'a, Agent>,
+        WriteStorage<'a, Elite>,
         WriteStorage<'a, Fitness>,
-        ReadExpect<'a, Ticks>,
-        ReadExpect<'a, ResetInterval>,
+        Write<'a, Parents>,
+        ReadExpect<'a, Generation>,
+        WriteExpect<'a, WorldRng>,
+        ReadExpect<'a, Config>,
     );
 
-    fn run(&mut self, (mut agents, fitnesses, ticks, interval): Self::SystemData) {
-        let interval = interval.0;
-        if ticks.get() % interval != 0 {
+    fn run(
+        &mut self,
+        (
+            entities,
+            mut agents,
+            mut elites,
+            fitnesses,
+            mut parents,
+            generation,
+            mut rng,
+            config,
+        ): Self::SystemData,
+    ) {
+        if generation.phase() != Phase::Breed {
             return;
         }
 
-        let networks: Vec<NetworkFitness> = (&agents, &fitnesses)
+        let genomes: HashMap<Entity, Genome> = (&entities, &agents)
             .join()
-            .map(|(agent, fitness)| NetworkFitness {
-                network: agent.network.clone(),
-                fitness: fitness.fitness,
+            .map(|(entity, agent)| {
+                let genome = Genome {
+                    network: agent.network.clone(),
+                    mutation: agent.mutation,
+                };
+                (entity, genome)
             })
             .collect();
+        let mut parents = std::mem::take(&mut parents.0).into_iter();
+
+        // The fittest agents keep their networks, ties going to whichever the world stores first
+        let mut ranked: Vec<(Entity, u32)> = (&entities, &fitnesses)
+            .join()
+            .map(|(entity, fitness)| (entity, fitness.fitness))
+            .collect();
+        ranked.sort_by_key(|&(_, fitness)| Reverse(fitness));
+        elites.clear();
+        for &(entity, _) in ranked.iter().take(config.selection.elites) {
+            elites.insert(entity, Elite).expect("Unable to mark elite");
+        }
+
+        let rng = &mut rng.0;
+        let crossover = &config.crossover;
 
-        let mut rng = thread_rng();
-
-        for agent in (&mut agents).join() {
-            let network_a = &networks
-                .choose_weighted(&mut rng, |n| n.fitness)
-                .unwrap()
-                .network;
-            let network_b = &networks
-                .choose_weighted(&mut rng, |n| n.fitness)
-                .unwrap()
-                .network
-                .clone();
-            agent.network = network_a.crossover_uniform(&mut rng, network_b);
+        for (agent, _) in (&mut agents, !&elites).join() {
+            let (a, b) = parents.next().expect("Selection picked too few parents");
+            let (a, b) = (&genomes[&a], &genomes[&b]);
+            agent.network = cross(rng, &a.network, &b.network, crossover);
+            agent.mutation = a.mutation.crossover_uniform(rng, &b.mutation);
         }
     }
 }
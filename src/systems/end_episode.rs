@@ -0,0 +1,43 @@
+use crate::components::{EpisodeResults, Performance, Score};
+use crate::config::Config;
+use crate::fitness::FitnessTerms;
+use crate::resources::{Generation, GenerationEvent};
+use specs::{prelude::*, ReadExpect, RunningTime, System, WriteStorage};
+
+// Records each agent's fitness terms at the end of every episode, then clears its score and performance
+// for the next one, leaving the last of the generation's for ResetScores
+pub struct EndEpisode;
+
+impl<'a> System<'a> for EndEpisode {
+    type SystemData = (
+        WriteStorage<'a, Score>,
+        WriteStorage<'a, Performance>,
+        WriteStorage<'a, EpisodeResults>,
+        ReadExpect<'a, Generation>,
+        ReadExpect<'a, Config>,
+    );
+
+    fn run(
+        &mut self,
+        (mut scores, mut performances, mut results, generation, config): Self::SystemData,
+    ) {
+        if !generation.ended(GenerationEvent::EpisodeEnded) {
+            return;
+        }
+
+        let seconds = config.simulation.population_lifetime_seconds as f32;
+        let last_episode = generation.ended(GenerationEvent::GenerationEnded);
+        for (score, performance, results) in (&mut scores, &mut performances, &mut results).join() {
+            let terms = FitnessTerms::new(score.score(), performance, seconds);
+            results.episodes.push(terms);
+            if !last_episode {
+                score.reset();
+                *performance = Performance::default();
+            }
+        }
+    }
+
+    fn running_time(&self) -> RunningTime {
+        RunningTime::VeryShort
+    }
+}
@@ -0,0 +1,261 @@
+use crate::components::{Agent, Elite, EpisodeResults, Fitness, Objectives, Performance};
+use crate::config::{Config, FitnessTerm, SelectionMethod, StatsConfig};
+use crate::fitness::FitnessTerms;
+use crate::resources::{Generation, Phase, Ticks};
+use crate::stats::{GenerationStats, Summary};
+use serde::Serialize;
+use specs::{prelude::*, ReadExpect, ReadStorage, RunningTime, System};
+use std::fs::{File, OpenOptions};
+use std::io::{self, BufWriter, Write};
+use std::path::Path;
+use std::time::Instant;
+
+#[derive(Serialize)]
+struct Record<'a> {
+    generation: u64,
+    ticks: u64,
+    #[serde(flatten)]
+    stats: &'a GenerationStats,
+    // wall-clock time spent on the generation
+    seconds: f32,
+    // targets hit over all of the generation's episodes, per agent in the same order as fitness_ranks
+    scores: &'a [u32],
+    // weighted sum of each agent's fitness terms, which selection ranks
+    fitness: &'a [f32],
+    fitness_terms: &'a [FitnessTerms],
+    // fitness assigned by selection
+    fitness_ranks: &'a [u32],
+    // whether each agent was carried over unchanged from the last generation rather than bred
+    elites: &'a [bool],
+    // mean score of the elites and of the offspring
+    elite_avg: f32,
+    offspring_avg: f32,
+    // distribution of the agents' mutation parameters
+    mutation_rate: &'a Summary,
+    mutation_sigma: &'a Summary,
+    // the non-dominated front when selection trades objectives off
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pareto_front: Option<ParetoFront<'a>>,
+}
+
+#[derive(Serialize)]
+struct ParetoFront<'a> {
+    objectives: &'a [FitnessTerm],
+    // an entry per agent on the front, its index in `scores` and its objectives
+    // (negated where lower is better, so that every objective is maximised)
+    agents: Vec<(usize, &'a [f32])>,
+}
+
+fn summary_csv(s: &Summary) -> String {
+    format!("{},{},{},{},{}", s.mean, s.median, s.min, s.max, s.std_dev)
+}
+
+// Writes a line of statistics per generation to CSV and/or JSON lines files
+// Files are appended to, so a resumed run carries on where it left off
+pub struct ExportStats {
+    csv: Option<BufWriter<File>>,
+    jsonl: Option<BufWriter<File>>,
+    generation_start: Instant,
+}
+
+fn open_append(path: &Path) -> io::Result<(BufWriter<File>, bool)> {
+    let file = OpenOptions::new().create(true).append(true).open(path)?;
+    let is_empty = file.metadata()?.len() == 0;
+    Ok((BufWriter::new(file), is_empty))
+}
+
+impl ExportStats {
+    // None if no export files are configured
+    pub fn from_config(config: &StatsConfig) -> io::Result<Option<Self>> {
+        if config.csv.is_none() && config.jsonl.is_none() {
+            return Ok(None);
+        }
+
+        let csv = match &config.csv {
+            Some(path) => {
+                let (mut w, is_empty) = open_append(path)?;
+                if is_empty {
+                    writeln!(
+                        w,
+                        "generation,ticks,avg,min,max,total,median,std_dev,seconds,fitness_ranks,\
+                         rate_mean,rate_median,rate_min,rate_max,rate_std_dev,\
+                         sigma_mean,sigma_median,sigma_min,sigma_max,sigma_std_dev,\
+                         elite_avg,offspring_avg,\
+                         fitness_mean,fitness_median,fitness_min,fitness_max,fitness_std_dev,\
+                         distance_mean,energy_mean,time_to_first_hit_mean,spinning_mean,novelty_mean,\
+                         pareto_front_size"
+                    )?;
+                }
+                Some(w)
+            }
+            None => None,
+        };
+        let jsonl = match &config.jsonl {
+            Some(path) => Some(open_append(path)?.0),
+            None => None,
+        };
+
+        Ok(Some(Self {
+            csv,
+            jsonl,
+            generation_start: Instant::now(),
+        }))
+    }
+
+    fn write(&mut self, record: &Record) -> io::Result<()> {
+        if let Some(w) = &mut self.csv {
+            let s = record.stats;
+            let ranks: Vec<String> = record.fitness_ranks.iter().map(u32::to_string).collect();
+            let terms = record.fitness_terms;
+            let term_mean = |term: fn(&FitnessTerms) -> f32| {
+                Summary::from_values(&terms.iter().map(term).collect::<Vec<f32>>()).mean
+            };
+            writeln!(
+                w,
+                "{},{},{},{},{},{},{},{},{},{},{},{},{},{},{},{},{},{},{},{},{}",
+                record.generation,
+                record.ticks,
+                s.avg,
+                s.min,
+                s.max,
+                s.total,
+                s.median,
+                s.std_dev,
+                record.seconds,
+                ranks.join(" "),
+                summary_csv(record.mutation_rate),
+                summary_csv(record.mutation_sigma),
+                record.elite_avg,
+                record.offspring_avg,
+                summary_csv(&Summary::from_values(record.fitness)),
+                term_mean(|t| t.distance),
+                term_mean(|t| t.energy),
+                term_mean(|t| t.time_to_first_hit),
+                term_mean(|t| t.spinning),
+                term_mean(|t| t.novelty),
+                record.pareto_front.as_ref().map_or(0, |f| f.agents.len())
+            )?;
+            w.flush()?;
+        }
+        if let Some(w) = &mut self.jsonl {
+            serde_json::to_writer(&mut *w, record)?;
+            writeln!(w)?;
+            w.flush()?;
+        }
+        Ok(())
+    }
+}
+
+impl<'a> System<'a> for ExportStats {
+    type SystemData = (
+        ReadStorage<'a, EpisodeResults>,
+        ReadStorage<'a, Fitness>,
+        ReadStorage<'a, Agent>,
+        ReadStorage<'a, Elite>,
+        ReadStorage<'a, Performance>,
+        ReadStorage<'a, Objectives>,
+        ReadExpect<'a, Ticks>,
+        ReadExpect<'a, Generation>,
+        ReadExpect<'a, Config>,
+    );
+
+    fn run(
+        &mut self,
+        (
+            results,
+            fitnesses,
+            agents,
+            elites,
+            performances,
+            objectives,
+            ticks,
+            generation,
+            config,
+        ): Self::SystemData,
+    ) {
+        // Nothing left to export to once writing has failed
+        if generation.phase() != Phase::Select || (self.csv.is_none() && self.jsonl.is_none()) {
+            return;
+        }
+
+        let aggregate = config.evaluation.aggregate;
+        let agents_stats: Vec<(FitnessTerms, u32, bool, Option<&Objectives>, u32)> = (
+            &results,
+            &performances,
+            &fitnesses,
+            elites.maybe(),
+            objectives.maybe(),
+        )
+            .join()
+            .map(|(r, p, f, elite, objectives)| {
+                let terms = FitnessTerms::for_generation(r, p, aggregate);
+                (terms, f.fitness, elite.is_some(), objectives, r.hits())
+            })
+            .collect();
+        let fitness_terms: Vec<FitnessTerms> = agents_stats.iter().map(|a| a.0.clone()).collect();
+        let fitness: Vec<f32> = fitness_terms
+            .iter()
+            .map(|t| t.fitness(&config.fitness))
+            .collect();
+        let scores: Vec<u32> = agents_stats.iter().map(|a| a.4).collect();
+        let fitness_ranks: Vec<u32> = agents_stats.iter().map(|a| a.1).collect();
+        let elites: Vec<bool> = agents_stats.iter().map(|a| a.2).collect();
+        let stats = GenerationStats::from_scores(&scores);
+        let mean_where = |elite: bool| {
+            let scores: Vec<f32> = scores
+                .iter()
+                .zip(&elites)
+                .filter(|&(_, &e)| e == elite)
+                .map(|(&s, _)| s as f32)
+                .collect();
+            Summary::from_values(&scores).mean
+        };
+        let (rates, sigmas): (Vec<f32>, Vec<f32>) = agents
+            .join()
+            .map(|a| (a.mutation.rate, a.mutation.sigma))
+            .unzip();
+        let pareto_front =
+            (config.selection.method == SelectionMethod::Nsga2).then(|| ParetoFront {
+                objectives: &config.selection.objectives,
+                agents: agents_stats
+                    .iter()
+                    .enumerate()
+                    .filter_map(|(i, a)| {
+                        a.3.filter(|o| o.rank.front == 0)
+                            .map(|o| (i, &o.values[..]))
+                    })
+                    .collect(),
+            });
+        let mutation_rate = Summary::from_values(&rates);
+        let mutation_sigma = Summary::from_values(&sigmas);
+        let seconds = self.generation_start.elapsed().as_secs_f32();
+        self.generation_start = Instant::now();
+
+        let record = Record {
+            generation: generation.completed(),
+            ticks: ticks.get(),
+            stats: &stats,
+            seconds,
+            scores: &scores,
+            fitness: &fitness,
+            fitness_terms: &fitness_terms,
+            fitness_ranks: &fitness_ranks,
+            elites: &elites,
+            elite_avg: mean_where(true),
+            offspring_avg: mean_where(false),
+            mutation_rate: &mutation_rate,
+            mutation_sigma: &mutation_sigma,
+            pareto_front,
+        };
+        // A failed write shouldn't end the run, so export stops instead, like a checkpoint that fails to save
+        if let Err(e) = self.write(&record) {
+            eprintln!("Unable to export stats, no more will be exported: {e}");
+            self.csv = None;
+            self.jsonl = None;
+        }
+    }
+
+    fn running_time(&self) -> RunningTime {
+        RunningTime::VeryShort
+    }
+}
@@ -0,0 +1,42 @@
+use crate::components::{Position, Target};
+use crate::config::Config;
+use crate::resources::{Generation, Phase};
+use specs::{prelude::*, ReadExpect, ReadStorage, RunningTime, System, WriteExpect};
+
+// Moves the generation on a tick, ending the episode once it has run its length or its targets are all gone
+pub struct AdvanceGeneration;
+
+impl<'a> System<'a> for AdvanceGeneration {
+    type SystemData = (
+        WriteExpect<'a, Generation>,
+        ReadStorage<'a, Target>,
+        ReadStorage<'a, Position>,
+        ReadExpect<'a, Config>,
+    );
+
+    fn run(&mut self, (mut generation, targets, positions, config): Self::SystemData) {
+        let cleared =
+            !config.simulation.respawn_targets && (&targets, &positions).join().next().is_none();
+        generation.tick(config.episode_ticks(), config.evaluation.episodes, cleared);
+    }
+
+    fn running_time(&self) -> RunningTime {
+        RunningTime::VeryShort
+    }
+}
+
+// Moves an ending generation on to the next phase
+// Has to be given every system of the phase before as a dependency, specs doesn't order systems by when they're added
+pub struct EnterPhase(pub Phase);
+
+impl<'a> System<'a> for EnterPhase {
+    type SystemData = WriteExpect<'a, Generation>;
+
+    fn run(&mut self, mut generation: Self::SystemData) {
+        generation.enter(self.0);
+    }
+
+    fn running_time(&self) -> RunningTime {
+        RunningTime::VeryShort
+    }
+}
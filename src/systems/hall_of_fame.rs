@@ -0,0 +1,83 @@
+use crate::components::{Agent, Elite, EpisodeResults, Performance};
+use crate::config::Config;
+use crate::fitness::FitnessTerms;
+use crate::hall_of_fame::{HallOfFame, Member};
+use crate::resources::{Generation, Phase, WorldRng};
+use rand::seq::SliceRandom;
+use specs::{prelude::*, ReadExpect, ReadStorage, RunningTime, System, WriteStorage};
+
+// Offers every network of the generation that just ended to the hall of fame
+pub struct UpdateHallOfFame;
+
+impl<'a> System<'a> for UpdateHallOfFame {
+    type SystemData = (
+        ReadStorage<'a, Agent>,
+        ReadStorage<'a, EpisodeResults>,
+        ReadStorage<'a, Performance>,
+        WriteExpect<'a, HallOfFame>,
+        ReadExpect<'a, Generation>,
+        ReadExpect<'a, Config>,
+    );
+
+    fn run(
+        &mut self,
+        (agents, results, performances, mut hall_of_fame, generation, config): Self::SystemData,
+    ) {
+        if hall_of_fame.capacity() == 0 || generation.phase() != Phase::Select {
+            return;
+        }
+
+        let aggregate = config.evaluation.aggregate;
+        for (agent, results, performance) in (&agents, &results, &performances).join() {
+            let terms = FitnessTerms::for_generation(results, performance, aggregate);
+            hall_of_fame.consider(Member {
+                generation: generation.number(),
+                score: results.hits(),
+                fitness: terms.fitness(&config.fitness),
+                seed: config.simulation.seed.unwrap_or_default(),
+                network: agent.network.clone(),
+            });
+        }
+    }
+
+    fn running_time(&self) -> RunningTime {
+        RunningTime::Short
+    }
+}
+
+// Replaces some of the new offspring with copies of random hall of fame members
+pub struct Reinject;
+
+impl<'a> System<'a> for Reinject {
+    type SystemData = (
+        Entities<'a>,
+        WriteStorage<'a, Agent>,
+        ReadStorage<'a, Elite>,
+        ReadExpect<'a, HallOfFame>,
+        ReadExpect<'a, Generation>,
+        ReadExpect<'a, Config>,
+        WriteExpect<'a, WorldRng>,
+    );
+
+    fn run(
+        &mut self,
+        (entities, mut agents, elites, hall_of_fame, generation, config, mut rng): Self::SystemData,
+    ) {
+        let reinject = config.hall_of_fame.reinject;
+        if reinject == 0 || hall_of_fame.members().is_empty() || generation.phase() != Phase::Breed
+        {
+            return;
+        }
+
+        let rng = &mut rng.0;
+        let offspring: Vec<Entity> = (&entities, &agents, !&elites)
+            .join()
+            .map(|(entity, _, _)| entity)
+            .collect();
+        for &entity in offspring.choose_multiple(rng, reinject) {
+            let member = hall_of_fame.members().choose(rng).unwrap();
+            let agent = agents.get_mut(entity).unwrap();
+            agent.network = member.network.clone();
+        }
+    }
+}
@@ -0,0 +1,51 @@
+use crate::components::{Agent, EpisodeResults, Fitness};
+use crate::config::Config;
+use crate::resources::{Generation, Island, Phase};
+use specs::{prelude::*, ReadExpect, ReadStorage, RunningTime, System};
+use std::cmp::Reverse;
+
+// Hands the generation's scores, and its fittest networks when they're due to migrate, to the island
+// model run the world is part of, which takes them once every island has reached the end of the generation
+pub struct Emigrate;
+
+impl<'a> System<'a> for Emigrate {
+    type SystemData = (
+        ReadStorage<'a, Agent>,
+        ReadStorage<'a, EpisodeResults>,
+        ReadStorage<'a, Fitness>,
+        Option<Write<'a, Island>>,
+        ReadExpect<'a, Generation>,
+        ReadExpect<'a, Config>,
+    );
+
+    fn run(&mut self, (agents, results, fitnesses, island, generation, config): Self::SystemData) {
+        let mut island = match island {
+            Some(island) if generation.phase() == Phase::Select => island,
+            _ => return,
+        };
+
+        island.scores = results.join().map(EpisodeResults::hits).collect();
+
+        let islands = &config.islands;
+        if islands.migration_interval == 0
+            || generation.completed() % islands.migration_interval != 0
+        {
+            return;
+        }
+        // Ranked like elites, ties going to whichever the world stores first
+        let mut ranked: Vec<(&Agent, u32)> = (&agents, &fitnesses)
+            .join()
+            .map(|(agent, fitness)| (agent, fitness.fitness))
+            .collect();
+        ranked.sort_by_key(|&(_, fitness)| Reverse(fitness));
+        island.emigrants = ranked
+            .into_iter()
+            .take(islands.migrants)
+            .map(|(agent, _)| agent.network.clone())
+            .collect();
+    }
+
+    fn running_time(&self) -> RunningTime {
+        RunningTime::Short
+    }
+}
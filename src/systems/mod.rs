@@ -3,13 +3,21 @@ pub mod apply_velocity;
 pub mod collision_check;
 pub mod control;
 pub mod crossover;
+pub mod end_episode;
+pub mod export_stats;
+pub mod generation;
+pub mod hall_of_fame;
+pub mod islands;
 pub mod mutate;
+pub mod novelty;
 pub mod print_stats;
-pub mod rank_selection;
 pub mod reset_positions;
 pub mod reset_scores;
 pub mod reset_velocities;
-pub mod roulette_selection;
+pub mod selection;
 pub mod spawn_new_targets;
+pub mod steady_state;
 pub mod tick_counter;
+pub mod track_behavior;
+pub mod track_performance;
 pub mod vision;
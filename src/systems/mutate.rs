@@ -1,27 +1,57 @@
-use crate::components::Agent;
-use crate::resources::{ResetInterval, Ticks};
-use rand::thread_rng;
-use specs::{prelude::*, ReadExpect, System, WriteStorage};
+use crate::components::{Agent, Elite, MutationParams};
+use crate::config::{Config, MutationConfig, MutationOperator};
+use crate::neural::Network;
+use crate::resources::{Generation, Phase, WorldRng};
+use rand::Rng;
+use specs::{prelude::*, ReadExpect, ReadStorage, System, WriteStorage};
+
+// Mutate a network by the configured operator, with the rate and sigma of `params`
+pub fn mutate_network<R: Rng + ?Sized>(
+    rng: &mut R,
+    network: &mut Network,
+    params: MutationParams,
+    mutation: &MutationConfig,
+) {
+    match mutation.operator {
+        MutationOperator::Uniform => network.mutate_uniform(rng, params.rate),
+        MutationOperator::Gaussian => {
+            network.mutate_gaussian(rng, params.rate, params.sigma, mutation.bounds)
+        }
+    }
+}
 
 pub struct Mutate;
 
 impl<'a> System<'a> for Mutate {
     type SystemData = (
         WriteStorage<'a, Agent>,
-        ReadExpect<'a, Ticks>,
-        ReadExpect<'a, ResetInterval>,
+        ReadStorage<'a, Elite>,
+        ReadExpect<'a, Generation>,
+        ReadExpect<'a, Config>,
+        WriteExpect<'a, WorldRng>,
     );
 
-    fn run(&mut self, (mut agents, ticks, interval): Self::SystemData) {
-        let interval = interval.0;
-        if ticks.get() % interval != 0 {
+    fn run(&mut self, (mut agents, elites, generation, config, mut rng): Self::SystemData) {
+        if generation.phase() != Phase::Breed {
             return;
         }
 
-        let mut rng = thread_rng();
+        let mutation = &config.mutation;
+        let rng = &mut rng.0;
+        // Elites are carried over exactly as they were
+        for (agent, _) in (&mut agents, !&elites).join() {
+            // Self-adaptive parameters are mutated first, so that they're judged by the network they produce
+            let params = if mutation.self_adaptive {
+                agent.mutation.adapt(rng, mutation.learning_rate);
+                agent.mutation
+            } else {
+                MutationParams {
+                    rate: mutation.probability,
+                    sigma: mutation.sigma,
+                }
+            };
 
-        for agent in (&mut agents).join() {
-            agent.network.mutate_uniform(&mut rng, 0.0125);
+            mutate_network(rng, &mut agent.network, params, mutation);
         }
     }
 }
@@ -0,0 +1,50 @@
+use crate::components::{Behavior, Performance};
+use crate::config::Config;
+use crate::novelty::{self, NoveltyArchive};
+use crate::resources::{Generation, Phase};
+use specs::{prelude::*, ReadExpect, ReadStorage, RunningTime, System, WriteStorage};
+
+// Scores each agent's novelty at the end of a generation, then archives the most novel behaviours
+pub struct EvaluateNovelty;
+
+impl<'a> System<'a> for EvaluateNovelty {
+    type SystemData = (
+        ReadStorage<'a, Behavior>,
+        WriteStorage<'a, Performance>,
+        WriteExpect<'a, NoveltyArchive>,
+        ReadExpect<'a, Generation>,
+        ReadExpect<'a, Config>,
+    );
+
+    fn run(
+        &mut self,
+        (behaviors, mut performances, mut archive, generation, config): Self::SystemData,
+    ) {
+        if !config.uses_novelty() || generation.phase() != Phase::Select {
+            return;
+        }
+
+        let descriptors: Vec<Vec<f32>> = (&behaviors, &performances)
+            .join()
+            .map(|(behavior, _)| behavior.descriptor.clone())
+            .collect();
+        // Scored against the archive as it was, before any of this generation joins it
+        let scores = novelty::novelty(&descriptors, &archive, config.novelty.k);
+        for ((_, performance), &score) in (&behaviors, &mut performances).join().zip(&scores) {
+            performance.novelty = score;
+        }
+
+        let mut most_novel: Vec<usize> = (0..descriptors.len()).collect();
+        most_novel.sort_by(|&a, &b| scores[b].total_cmp(&scores[a]));
+        for i in most_novel
+            .into_iter()
+            .take(config.novelty.archive_per_generation)
+        {
+            archive.add(descriptors[i].clone());
+        }
+    }
+
+    fn running_time(&self) -> RunningTime {
+        RunningTime::Average
+    }
+}
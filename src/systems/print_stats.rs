@@ -1,41 +1,42 @@
-use crate::components::Score;
-use crate::resources::{ResetInterval, Ticks};
+use crate::components::{Agent, EpisodeResults};
+use crate::config::Config;
+use crate::resources::{Generation, Phase};
+use crate::stats::{GenerationStats, Summary};
 use specs::{prelude::*, ReadExpect, ReadStorage, RunningTime, System};
 
 pub struct PrintStats;
 
 impl<'a> System<'a> for PrintStats {
     type SystemData = (
-        ReadStorage<'a, Score>,
-        ReadExpect<'a, Ticks>,
-        ReadExpect<'a, ResetInterval>,
+        ReadStorage<'a, EpisodeResults>,
+        ReadStorage<'a, Agent>,
+        ReadExpect<'a, Generation>,
+        ReadExpect<'a, Config>,
     );
 
-    fn run(&mut self, (scores, ticks, interval): Self::SystemData) {
-        let interval = interval.0;
-        if ticks.get() % interval != 0 {
+    fn run(&mut self, (results, agents, generation, config): Self::SystemData) {
+        if !config.stats.print || generation.phase() != Phase::Select {
             return;
         }
-        let mut total = 0;
-        let mut max = 0;
-        let mut min = u32::MAX;
-        let mut num = 0;
-        for s in scores.join() {
-            let s = s.score();
-            total += s;
-            max = max.max(s);
-            min = min.min(s);
-            num += 1;
-        }
-        let avg = total as f32 / num as f32;
+        let scores: Vec<u32> = results.join().map(EpisodeResults::hits).collect();
+        let stats = GenerationStats::from_scores(&scores);
+
+        // Self-adaptive runs also show where the evolved mutation rate has drifted to
+        let rate = if config.mutation.self_adaptive {
+            let rates: Vec<f32> = agents.join().map(|a| a.mutation.rate).collect();
+            format!(" - Rate: {:.4}", Summary::from_values(&rates).mean)
+        } else {
+            String::new()
+        };
 
         println!(
-            "Gen {} = Avg: {:.2} - Min: {} - Max: {} - Total: {}",
-            ticks.get() / interval,
-            avg,
-            min,
-            max,
-            total
+            "Gen {} = Avg: {:.2} - Min: {} - Max: {} - Total: {}{}",
+            generation.completed(),
+            stats.avg,
+            stats.min,
+            stats.max,
+            stats.total,
+            rate
         );
     }
 
@@ -1,35 +1,56 @@
-use crate::components::Position;
-use crate::resources::{MaxPos, ResetInterval, Ticks};
-use rand::{
-    distributions::{Distribution, Uniform},
-    thread_rng,
-};
-use specs::{prelude::*, ReadExpect, System, WriteStorage};
+use crate::components::{Obstacle, Position, Target};
+use crate::obstacles::{self, Shape};
+use crate::resources::{Generation, GenerationEvent, MaxPos, WorldRng};
+use rand::distributions::{Distribution, Uniform};
+use specs::{prelude::*, ReadExpect, ReadStorage, System, WriteStorage};
 
+// Scatters agents and targets at the start of every episode, bringing back any targets that were taken out
 pub struct ResetPositions;
 
 impl<'a> System<'a> for ResetPositions {
     type SystemData = (
+        Entities<'a>,
         WriteStorage<'a, Position>,
-        ReadExpect<'a, Ticks>,
-        ReadExpect<'a, ResetInterval>,
+        ReadStorage<'a, Target>,
+        ReadStorage<'a, Obstacle>,
+        ReadExpect<'a, Generation>,
         ReadExpect<'a, MaxPos>,
+        WriteExpect<'a, WorldRng>,
     );
 
-    fn run(&mut self, (mut positions, ticks, interval, max): Self::SystemData) {
-        let interval = interval.0;
-        if ticks.get() % interval != 0 {
+    fn run(
+        &mut self,
+        (entities, mut positions, targets, obstacles, generation, max, mut rng): Self::SystemData,
+    ) {
+        if !generation.ended(GenerationEvent::EpisodeEnded) {
             return;
         }
         let max = max.0;
 
         let x_range = Uniform::from(0.0..max.x);
         let y_range = Uniform::from(0.0..max.y);
-        let mut rng = thread_rng();
+        let rng = &mut rng.0;
+        let shapes: Vec<Shape> = obstacles.join().map(|o| o.shape).collect();
+        let mut sample = || {
+            obstacles::place(&shapes, &max, || Position {
+                x: x_range.sample(rng),
+                y: y_range.sample(rng),
+            })
+        };
 
         for p in (&mut positions).join() {
-            p.x = x_range.sample(&mut rng);
-            p.y = y_range.sample(&mut rng);
+            *p = sample();
+        }
+
+        // Targets that were hit without respawning, placed after the rest
+        let gone: Vec<Entity> = (&entities, &targets, !&positions)
+            .join()
+            .map(|(entity, _, _)| entity)
+            .collect();
+        for target in gone {
+            positions
+                .insert(target, sample())
+                .expect("Unable to place target");
         }
     }
 }
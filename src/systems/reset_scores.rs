@@ -1,24 +1,38 @@
-use crate::components::Score;
-use crate::resources::{ResetInterval, Ticks};
+use crate::components::{Behavior, EpisodeResults, Performance, Score};
+use crate::resources::{Generation, Phase};
 use specs::{prelude::*, ReadExpect, RunningTime, System, WriteStorage};
 
+// Clears scores, and the rest of the agents' performances, episode results and behaviours, for a new generation
 pub struct ResetScores;
 
 impl<'a> System<'a> for ResetScores {
     type SystemData = (
         WriteStorage<'a, Score>,
-        ReadExpect<'a, Ticks>,
-        ReadExpect<'a, ResetInterval>,
+        WriteStorage<'a, Performance>,
+        WriteStorage<'a, EpisodeResults>,
+        WriteStorage<'a, Behavior>,
+        ReadExpect<'a, Generation>,
     );
 
-    fn run(&mut self, (mut scores, ticks, interval): Self::SystemData) {
-        let interval = interval.0;
-        if ticks.get() % interval != 0 {
+    fn run(
+        &mut self,
+        (mut scores, mut performances, mut results, mut behaviors, generation): Self::SystemData,
+    ) {
+        if generation.phase() != Phase::Reset {
             return;
         }
         for s in (&mut scores).join() {
             s.reset();
         }
+        for p in (&mut performances).join() {
+            *p = Performance::default();
+        }
+        for r in (&mut results).join() {
+            r.episodes.clear();
+        }
+        for b in (&mut behaviors).join() {
+            b.descriptor.clear();
+        }
     }
 
     fn running_time(&self) -> RunningTime {
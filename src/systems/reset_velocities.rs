@@ -1,34 +1,34 @@
 use crate::components::Velocity;
-use crate::resources::{ResetInterval, Ticks};
-use rand::{
-    distributions::{Distribution, Uniform},
-    thread_rng,
-};
+use crate::config::Config;
+use crate::resources::{Generation, GenerationEvent, WorldRng};
+use rand::distributions::{Distribution, Uniform};
 use specs::{prelude::*, ReadExpect, System, WriteStorage};
 use std::f32::consts::PI;
 
+// Gives agents new headings and speeds at the start of every episode
 pub struct ResetVelocities;
 
 impl<'a> System<'a> for ResetVelocities {
     type SystemData = (
         WriteStorage<'a, Velocity>,
-        ReadExpect<'a, Ticks>,
-        ReadExpect<'a, ResetInterval>,
+        ReadExpect<'a, Generation>,
+        ReadExpect<'a, Config>,
+        WriteExpect<'a, WorldRng>,
     );
 
-    fn run(&mut self, (mut velocities, ticks, interval): Self::SystemData) {
-        let interval = interval.0;
-        if ticks.get() % interval != 0 {
+    fn run(&mut self, (mut velocities, generation, config, mut rng): Self::SystemData) {
+        if !generation.ended(GenerationEvent::EpisodeEnded) {
             return;
         }
 
         let heading_range = Uniform::from(0.0..(2.0 * PI));
-        let magnitude_range = Uniform::from(5.0..100.0);
-        let mut rng = thread_rng();
+        let (min_magnitude, max_magnitude) = config.velocity.reset_magnitude;
+        let magnitude_range = Uniform::from(min_magnitude..max_magnitude);
+        let rng = &mut rng.0;
 
         for v in (&mut velocities).join() {
-            v.heading = heading_range.sample(&mut rng);
-            v.magnitude = magnitude_range.sample(&mut rng);
+            v.heading = heading_range.sample(rng);
+            v.magnitude = magnitude_range.sample(rng);
         }
     }
 }
@@ -0,0 +1,102 @@
+use crate::components::{EpisodeResults, Fitness, Objectives, Performance};
+use crate::config::{Config, SelectionMethod};
+use crate::fitness::FitnessTerms;
+use crate::pareto;
+use crate::resources::{Generation, Parents, Phase, WorldRng};
+use crate::selection::{self, SelectionStrategy};
+use specs::{prelude::*, ReadExpect, ReadStorage, RunningTime, System, WriteStorage};
+
+// Ranks the generation and has its strategy pick the parents of every agent that isn't an elite
+pub struct Selection {
+    strategy: Box<dyn SelectionStrategy>,
+}
+
+impl Selection {
+    pub fn new(strategy: Box<dyn SelectionStrategy>) -> Self {
+        Self { strategy }
+    }
+
+    pub fn from_config(config: &Config) -> Self {
+        Self::new(selection::from_config(&config.selection))
+    }
+}
+
+impl<'a> System<'a> for Selection {
+    type SystemData = (
+        Entities<'a>,
+        ReadStorage<'a, EpisodeResults>,
+        ReadStorage<'a, Performance>,
+        WriteStorage<'a, Fitness>,
+        WriteStorage<'a, Objectives>,
+        Write<'a, Parents>,
+        ReadExpect<'a, Generation>,
+        ReadExpect<'a, Config>,
+        WriteExpect<'a, WorldRng>,
+    );
+
+    fn run(
+        &mut self,
+        (
+            entities,
+            results,
+            performances,
+            mut fitnesses,
+            mut objectives,
+            mut parents,
+            generation,
+            config,
+            mut rng,
+        ): Self::SystemData,
+    ) {
+        if generation.phase() != Phase::Select {
+            return;
+        }
+
+        let aggregate = config.evaluation.aggregate;
+        let (agents, terms): (Vec<Entity>, Vec<FitnessTerms>) =
+            (&entities, &results, &performances)
+                .join()
+                .map(|(entity, results, performance)| {
+                    let terms = FitnessTerms::for_generation(results, performance, aggregate);
+                    (entity, terms)
+                })
+                .unzip();
+
+        let scores: Vec<f32> = if config.selection.method == SelectionMethod::Nsga2 {
+            // Rank by Pareto front and crowding distance, keeping each agent's objectives for stats to go by
+            let values: Vec<Vec<f32>> = terms
+                .iter()
+                .map(|t| t.objectives(&config.selection.objectives))
+                .collect();
+            let ranks = pareto::rank(&values);
+            let scores = pareto::crowded_scores(&ranks);
+            for ((&entity, values), rank) in agents.iter().zip(values).zip(ranks) {
+                objectives
+                    .insert(entity, Objectives { values, rank })
+                    .expect("Unable to overwrite objectives");
+            }
+            scores
+        } else {
+            terms.iter().map(|t| t.fitness(&config.fitness)).collect()
+        };
+
+        // Fitness is the rank of the score whichever strategy picks the parents, for elitism and stats to go by
+        for (&entity, fitness) in agents.iter().zip(selection::dense_ranks(&scores)) {
+            fitnesses
+                .insert(entity, Fitness { fitness })
+                .expect("Unable to overwrite fitness");
+        }
+
+        let num_pairs = agents.len().saturating_sub(config.selection.elites);
+        parents.0 = self
+            .strategy
+            .select(&mut rng.0, &scores, num_pairs)
+            .into_iter()
+            .map(|(a, b)| (agents[a], agents[b]))
+            .collect();
+    }
+
+    fn running_time(&self) -> RunningTime {
+        RunningTime::Short
+    }
+}
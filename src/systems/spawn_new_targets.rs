@@ -1,8 +1,11 @@
-use crate::components::Position;
-use crate::resources::{HitTargets, MaxPos};
-use rand::{thread_rng, Rng};
+use crate::components::{Obstacle, Position};
+use crate::config::Config;
+use crate::obstacles::{self, Shape};
+use crate::resources::{HitTargets, MaxPos, WorldRng};
+use rand::Rng;
 use specs::{prelude::*, RunningTime, System, WriteStorage};
 
+// Moves hit targets somewhere new, or takes them out of the arena until the next episode when they don't respawn
 pub struct SpawnNewTargets;
 
 impl<'a> System<'a> for SpawnNewTargets {
@@ -10,17 +13,36 @@ impl<'a> System<'a> for SpawnNewTargets {
         WriteStorage<'a, Position>,
         WriteExpect<'a, HitTargets>,
         ReadExpect<'a, MaxPos>,
+        WriteExpect<'a, WorldRng>,
+        ReadStorage<'a, Obstacle>,
+        ReadExpect<'a, Config>,
         Entities<'a>,
     );
 
-    fn run(&mut self, (mut position, mut hit_targets, max, entities): Self::SystemData) {
+    fn run(
+        &mut self,
+        (mut position, mut hit_targets, max, mut rng, obstacles, config, entities): Self::SystemData,
+    ) {
         let max = max.0;
-        hit_targets.0.drain().for_each(|id| {
-            let t = entities.entity(id);
-            let pos = position.get_mut(t).expect("Unable to find old target");
-            pos.x = thread_rng().gen_range(0.0..max.x);
-            pos.y = thread_rng().gen_range(0.0..max.y);
-        });
+        let rng = &mut rng.0;
+        let respawn = config.simulation.respawn_targets;
+        let shapes: Vec<Shape> = obstacles.join().map(|o| o.shape).collect();
+        std::mem::take(&mut hit_targets.0)
+            .into_iter()
+            .for_each(|id| {
+                let t = entities.entity(id);
+                if !respawn {
+                    // Without a position it's out of sight and reach, the entity is kept so that entities are
+                    // never reused in a different order (see checkpoint)
+                    position.remove(t);
+                    return;
+                }
+                let pos = position.get_mut(t).expect("Unable to find old target");
+                *pos = obstacles::place(&shapes, &max, || Position {
+                    x: rng.gen_range(0.0..max.x),
+                    y: rng.gen_range(0.0..max.y),
+                });
+            });
     }
 
     fn running_time(&self) -> RunningTime {
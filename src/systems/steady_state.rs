@@ -0,0 +1,154 @@
+use crate::components::{Age, Agent, Behavior, MutationParams, Performance, Score};
+use crate::config::Config;
+use crate::fitness::FitnessTerms;
+use crate::resources::{DeltaTime, Ticks, WorldRng};
+use crate::selection::{SelectionStrategy, Tournament};
+use crate::stats::RollingStats;
+use crate::systems::{crossover::cross, mutate::mutate_network};
+use specs::{prelude::*, ReadExpect, RunningTime, System, WriteStorage};
+
+// Ages every agent, and replaces each one that has outlived its lifetime with a child of two living
+// agents picked by tournament, in the same place and without disturbing anyone else
+pub struct SteadyState;
+
+impl<'a> System<'a> for SteadyState {
+    type SystemData = (
+        Entities<'a>,
+        WriteStorage<'a, Agent>,
+        WriteStorage<'a, Age>,
+        WriteStorage<'a, Score>,
+        WriteStorage<'a, Performance>,
+        WriteStorage<'a, Behavior>,
+        WriteExpect<'a, RollingStats>,
+        ReadExpect<'a, DeltaTime>,
+        ReadExpect<'a, Config>,
+        WriteExpect<'a, WorldRng>,
+    );
+
+    fn run(
+        &mut self,
+        (
+            entities,
+            mut agents,
+            mut ages,
+            mut scores,
+            mut performances,
+            mut behaviors,
+            mut rolling,
+            delta,
+            config,
+            mut rng,
+        ): Self::SystemData,
+    ) {
+        let lifetime = config
+            .steady_state
+            .lifetime_ticks(config.simulation.tickrate);
+        let mut expired = vec![];
+        for (entity, age) in (&entities, &mut ages).join() {
+            age.ticks += 1;
+            if age.ticks >= lifetime {
+                expired.push(entity);
+            }
+        }
+        if expired.is_empty() {
+            return;
+        }
+
+        // Living agents are compared by fitness per second, so that the young aren't beaten just for being young
+        let (living, rates): (Vec<Entity>, Vec<f32>) = (&entities, &ages, &scores, &performances)
+            .join()
+            .filter(|(_, age, _, _)| age.ticks < lifetime)
+            .map(|(entity, age, score, performance)| {
+                let seconds = age.ticks as f32 * delta.0;
+                let terms = FitnessTerms::new(score.score(), performance, seconds);
+                (
+                    entity,
+                    terms.fitness(&config.fitness) / seconds.max(delta.0),
+                )
+            })
+            .unzip();
+
+        let tournament = Tournament {
+            size: config.selection.tournament_size,
+            probability: config.selection.tournament_probability,
+        };
+        let mutation = &config.mutation;
+        let rng = &mut rng.0;
+        for entity in expired {
+            rolling.record(scores.get(entity).map_or(0, Score::score));
+
+            // With every agent expiring at once the child is bred from those that just expired instead
+            let (a, b) = if living.is_empty() {
+                (entity, entity)
+            } else {
+                let (a, b) = tournament.select(rng, &rates, 1)[0];
+                (living[a], living[b])
+            };
+            let (a, b) = (&agents.get(a).unwrap(), &agents.get(b).unwrap());
+            let mut network = cross(rng, &a.network, &b.network, &config.crossover);
+            let mut params = a.mutation.crossover_uniform(rng, &b.mutation);
+            if mutation.self_adaptive {
+                params.adapt(rng, mutation.learning_rate);
+            }
+            let rates = if mutation.self_adaptive {
+                params
+            } else {
+                MutationParams {
+                    rate: mutation.probability,
+                    sigma: mutation.sigma,
+                }
+            };
+            mutate_network(rng, &mut network, rates, mutation);
+
+            let agent = agents.get_mut(entity).unwrap();
+            agent.network = network;
+            agent.mutation = params;
+            ages.insert(entity, Age::default())
+                .expect("Unable to reset age");
+            scores.get_mut(entity).unwrap().reset();
+            performances
+                .insert(entity, Performance::default())
+                .expect("Unable to reset performance");
+            if let Some(behavior) = behaviors.get_mut(entity) {
+                behavior.descriptor.clear();
+            }
+            rolling.births += 1;
+        }
+    }
+
+    fn running_time(&self) -> RunningTime {
+        RunningTime::Short
+    }
+}
+
+// Prints statistics of the most recent lifetimes every `steady_state.report_seconds`
+pub struct PrintRollingStats;
+
+impl<'a> System<'a> for PrintRollingStats {
+    type SystemData = (
+        ReadExpect<'a, RollingStats>,
+        ReadExpect<'a, Ticks>,
+        ReadExpect<'a, Config>,
+    );
+
+    fn run(&mut self, (rolling, ticks, config): Self::SystemData) {
+        let every = config.steady_state.report_seconds * config.simulation.tickrate as u64;
+        if !config.stats.print || ticks.get() % every != 0 || rolling.is_empty() {
+            return;
+        }
+        let stats = rolling.stats();
+        println!(
+            "Tick {} = Last {} lives - Avg: {:.2} - Min: {} - Max: {} - Births: {}",
+            ticks.get(),
+            rolling.len(),
+            stats.avg,
+            stats.min,
+            stats.max,
+            rolling.births
+        );
+    }
+
+    fn running_time(&self) -> RunningTime {
+        RunningTime::VeryShort
+    }
+}
@@ -0,0 +1,68 @@
+use crate::components::{Behavior, Force, Position};
+use crate::config::{BehaviorDescriptor, Config};
+use crate::novelty;
+use crate::resources::{Generation, GenerationEvent, MaxPos};
+use specs::{prelude::*, ReadExpect, ReadStorage, System, WriteStorage};
+
+// Builds each agent's behaviour descriptor over a generation, when novelty is in use
+pub struct TrackBehavior;
+
+impl<'a> System<'a> for TrackBehavior {
+    type SystemData = (
+        ReadStorage<'a, Position>,
+        ReadStorage<'a, Force>,
+        WriteStorage<'a, Behavior>,
+        ReadExpect<'a, MaxPos>,
+        ReadExpect<'a, Generation>,
+        ReadExpect<'a, Config>,
+    );
+
+    fn run(
+        &mut self,
+        (positions, forces, mut behaviors, max, generation, config): Self::SystemData,
+    ) {
+        if !config.uses_novelty() {
+            return;
+        }
+        // Generations are sampled over their full length, even when they end early
+        let interval = config.reset_interval();
+        let novelty = &config.novelty;
+        let tick = generation.ticks();
+
+        match novelty.descriptor {
+            BehaviorDescriptor::FinalPosition | BehaviorDescriptor::Trajectory => {
+                let samples = match novelty.descriptor {
+                    BehaviorDescriptor::FinalPosition => 1,
+                    _ => novelty.samples as u64,
+                };
+                // Sampled on the ticks that cross into the next of `samples` equal spans, the last being the final tick
+                // A generation that ends early has its missing samples filled in with where agents ended up
+                let sampled = tick * samples / interval != (tick - 1) * samples / interval;
+                let ended = generation.ended(GenerationEvent::GenerationEnded);
+                if !sampled && !ended {
+                    return;
+                }
+                for (position, behavior) in (&positions, &mut behaviors).join() {
+                    let descriptor = novelty::position_descriptor(position, &max.0);
+                    if sampled {
+                        behavior.descriptor.extend(descriptor);
+                    }
+                    while ended && behavior.descriptor.len() < samples as usize * descriptor.len() {
+                        behavior.descriptor.extend(descriptor);
+                    }
+                }
+            }
+            BehaviorDescriptor::Forces => {
+                let bins = novelty.bins;
+                // Fractions of the generation, so that the histogram sums to 1 by the end of a full length one
+                let share = 1.0 / interval as f32;
+                for (force, behavior) in (&forces, &mut behaviors).join() {
+                    if behavior.descriptor.is_empty() {
+                        behavior.descriptor.resize(bins * bins, 0.0);
+                    }
+                    behavior.descriptor[novelty::force_bin(force, bins)] += share;
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,91 @@
+use crate::components::{Age, Force, Performance, Score, Velocity};
+use crate::config::Config;
+use crate::resources::{DeltaTime, Generation};
+use specs::{prelude::*, ReadExpect, ReadStorage, System, WriteStorage};
+
+// Accumulates what each agent does over a generation for its fitness terms
+pub struct TrackPerformance;
+
+impl<'a> System<'a> for TrackPerformance {
+    type SystemData = (
+        ReadStorage<'a, Score>,
+        ReadStorage<'a, Velocity>,
+        ReadStorage<'a, Force>,
+        WriteStorage<'a, Performance>,
+        ReadStorage<'a, Age>,
+        ReadExpect<'a, DeltaTime>,
+        ReadExpect<'a, Generation>,
+        ReadExpect<'a, Config>,
+    );
+
+    fn run(
+        &mut self,
+        (scores, velocities, forces, mut performances, ages, delta, generation, config): Self::SystemData,
+    ) {
+        let delta = delta.0;
+        let fitness = &config.fitness;
+        // Agents replaced one at a time are timed from their birth rather than the episode's start
+        let steady_state = config.steady_state.enabled;
+
+        for (score, velocity, force, performance, age) in (
+            &scores,
+            &velocities,
+            &forces,
+            &mut performances,
+            ages.maybe(),
+        )
+            .join()
+        {
+            let ticks = match age {
+                Some(age) if steady_state => age.ticks + 1,
+                _ => generation.episode_ticks(),
+            };
+            let elapsed = ticks as f32 * delta;
+            performance.distance += velocity.magnitude * delta;
+            performance.energy += force.rotation.hypot(force.translation) * delta;
+            if performance.first_hit.is_none() && score.score() > 0 {
+                performance.first_hit = Some(elapsed);
+            }
+            if velocity.magnitude < fitness.spin_speed
+                && force.rotation.abs() > fitness.spin_rotation
+            {
+                performance.spinning += delta;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn late_born_agents_are_timed_from_birth() {
+        let mut config = Config::default();
+        config.steady_state.enabled = true;
+        let mut world = World::new();
+        world.register::<Score>();
+        world.register::<Velocity>();
+        world.register::<Force>();
+        world.register::<Performance>();
+        world.register::<Age>();
+        world.insert(DeltaTime(0.5));
+        world.insert(Generation::new(0, 0, 100, 100, None));
+        world.insert(config);
+        let agent = world
+            .create_entity()
+            .with(Score::with_score(1))
+            .with(Velocity {
+                heading: 0.0,
+                magnitude: 0.0,
+            })
+            .with(Force::default())
+            .with(Performance::default())
+            .with(Age { ticks: 3 })
+            .build();
+
+        TrackPerformance.run_now(&world);
+        let performances = world.read_storage::<Performance>();
+        assert_eq!(performances.get(agent).unwrap().first_hit, Some(2.0));
+    }
+}
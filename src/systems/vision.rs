@@ -1,4 +1,6 @@
-use crate::components::{Agent, Position, Target, Velocity};
+use crate::components::{Agent, Obstacle, Position, Target, Velocity};
+use crate::config::Config;
+use crate::obstacles::{self, Shape};
 use crate::resources::MaxPos;
 use specs::{prelude::*, ReadStorage, RunningTime, System, WriteStorage};
 use std::f32::consts::PI;
@@ -8,6 +10,18 @@ struct DistanceAngle {
     angle: f32,
 }
 
+// [0, 1) exponentially falling off from 1 to 0 with distance (closer => 1)
+fn proximity(distance: f32, viewing_distance: f32) -> f32 {
+    let ln_offset = 1.0 / (5.0 * 4.0_f32.ln());
+    // [0, 1], as linear function of distance to target (farther => 1)
+    let linear = (distance / viewing_distance).min(1.0);
+    // 1/(5*ln(3*x+1)) - 1(5*ln(4))
+    // https://www.wolframalpha.com/input/?i=1%2F%285*ln%283*x%2B1%29%29-%281%2F%285*ln%284%29%29%29+for+x+%3D+0+to+1
+    (1.0 / (5.0 * (3.0_f32.mul_add(linear, 1.0)).ln()) - ln_offset).clamp(0.0, 1.0)
+}
+
+// Fills each agent's network inputs with what its receptors see, targets first and then, when obstacles
+// are sensed, how near the obstacle straight ahead of each receptor is
 pub struct Vision;
 
 impl<'a> System<'a> for Vision {
@@ -17,15 +31,26 @@ impl<'a> System<'a> for Vision {
         ReadStorage<'a, Position>,
         ReadStorage<'a, Velocity>,
         ReadStorage<'a, Target>,
+        ReadStorage<'a, Obstacle>,
         ReadExpect<'a, MaxPos>,
+        ReadExpect<'a, Config>,
     );
 
-    fn run(&mut self, (mut agents, positions, velocities, targets, max): Self::SystemData) {
-        let viewing_distance = 800.0; // distance that an agent can see a target
-        let vision_cone = PI; // agent field of view in radians
+    fn run(
+        &mut self,
+        (mut agents, positions, velocities, targets, obstacles, max, config): Self::SystemData,
+    ) {
+        let viewing_distance = config.vision.viewing_distance;
+        let vision_cone = config.vision.vision_cone;
+        let sense = config.obstacles.sense;
+        let shapes: Vec<Shape> = obstacles.join().map(|o| o.shape).collect();
         for (agent, agent_pos, agent_velocity) in (&mut agents, &positions, &velocities).join() {
             let max = max.0;
-            let num_receptors = agent.network.input_size();
+            let num_receptors = if sense {
+                agent.network.input_size() / 2
+            } else {
+                agent.network.input_size()
+            };
             // individual vision receptor field of view
             let cone_slice = vision_cone / num_receptors as f32;
             // start and end of field of view
@@ -85,7 +110,11 @@ impl<'a> System<'a> for Vision {
                         angle += 2.0 * PI;
                     }
                     angle = agent_velocity.heading - angle;
-                    if angle >= start && angle < end {
+                    // targets behind an obstacle are hidden by it
+                    if angle >= start
+                        && angle < end
+                        && obstacles::first_hit(&shapes, agent_pos, target_pos, &max).is_none()
+                    {
                         Some(DistanceAngle {
                             distance: target_pos.distance(agent_pos),
                             angle,
@@ -98,9 +127,7 @@ impl<'a> System<'a> for Vision {
             // sort targets by distance from agent
             visible_targets.sort_by(|a, b| a.distance.partial_cmp(&(b.distance)).unwrap());
 
-            let ln_offset = 1.0 / (5.0 * 4.0_f32.ln());
-
-            let neuron_inputs: Vec<f32> = (0..num_receptors)
+            let mut neuron_inputs: Vec<f32> = (0..num_receptors)
                 .map(|i| {
                     // start and end of field of view for this receptor
                     let slice_start = start + (cone_slice * i as f32);
@@ -116,20 +143,27 @@ impl<'a> System<'a> for Vision {
                         }
                     }
                     // sum up [0, 1) for each target based on distance to target (closer => 1)
-                    seen_targets.iter().fold(0.0, |acc, t| {
-                        // [0, 1], as linear function of distance to target (farther => 1)
-                        let linear = (t.distance / viewing_distance).min(1.0);
-                        // [0, 1) exponentially falling off from 1 to 0 (closer => 1)
-                        // 1/(5*ln(3*x+1)) - 1(5*ln(4))
-                        // https://www.wolframalpha.com/input/?i=1%2F%285*ln%283*x%2B1%29%29-%281%2F%285*ln%284%29%29%29+for+x+%3D+0+to+1
-                        let exponential = (1.0 / (5.0 * (3.0_f32.mul_add(linear, 1.0)).ln())
-                            - ln_offset)
-                            .clamp(0.0, 1.0);
-                        acc + exponential
-                    })
+                    seen_targets
+                        .iter()
+                        .fold(0.0, |acc, t| acc + proximity(t.distance, viewing_distance))
                 })
                 .collect();
 
+            if sense {
+                // a ray out of the middle of each receptor's field of view, as far as the agent can see
+                neuron_inputs.extend((0..num_receptors).map(|i| {
+                    let angle = start + cone_slice * (i as f32 + 0.5);
+                    let (sin, cos) = (agent_velocity.heading - angle).sin_cos();
+                    let ray_end = Position {
+                        x: cos.mul_add(viewing_distance, agent_pos.x),
+                        y: sin.mul_add(viewing_distance, agent_pos.y),
+                    };
+                    obstacles::first_hit(&shapes, agent_pos, &ray_end, &max).map_or(0.0, |hit| {
+                        proximity(hit.t * viewing_distance, viewing_distance)
+                    })
+                }));
+            }
+
             agent.inputs = Some(neuron_inputs);
         }
     }